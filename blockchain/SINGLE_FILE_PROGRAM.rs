@@ -220,20 +220,11 @@ pub mod loan_management {
         let payment_record = &mut ctx.accounts.payment_record;
         let clock = Clock::get()?;
 
-        let due_date = loan.start_timestamp + ((installment_number as i64) * 30 * 24 * 60 * 60);
-        let days_late = if clock.unix_timestamp > due_date {
-            ((clock.unix_timestamp - due_date) / (24 * 60 * 60)) as u16
-        } else {
-            0
-        };
-
-        let fine_amount = if days_late > 0 {
-            (loan.monthly_installment * (days_late as u64) / 100).min(loan.monthly_installment / 10)
-        } else {
-            0
-        };
+        let due_date = loan.start_timestamp + ((installment_number as i64) * 30 * SECONDS_PER_DAY);
+        let days_late = days_late_after_grace(due_date, clock.unix_timestamp, GRACE_PERIOD_SECONDS);
+        let fine_amount = compute_fine(loan.monthly_installment, days_late, DAILY_FINE_BPS, FINE_CAP_BPS, MIN_FINE_CAP);
 
-        let on_time = days_late == 0;
+        let on_time = clock.unix_timestamp <= due_date + GRACE_PERIOD_SECONDS;
 
         payment_record.loan = loan_key;
         payment_record.user = user_key;
@@ -423,6 +414,33 @@ pub mod loan_management {
     }
 }
 
+// ============================================================================
+// FINE MODEL
+// ============================================================================
+// Identical to state.rs in the modular program, where the grace period and daily
+// rate are program-state config; here they stay fixed at the modular defaults.
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+pub const GRACE_PERIOD_SECONDS: i64 = 2 * SECONDS_PER_DAY;
+pub const DAILY_FINE_BPS: u16 = 50; // 0.5% of the installment per day
+pub const FINE_CAP_BPS: u16 = 1000; // one installment's fine never exceeds 10% of it
+pub const MIN_FINE_CAP: u64 = 0; // no floor under the cap
+
+/// Whole days an installment is past its grace window (0 while still inside it)
+pub fn days_late_after_grace(due_timestamp: i64, now: i64, grace_period_seconds: i64) -> u16 {
+    let seconds_past_grace = (now - due_timestamp - grace_period_seconds).max(0);
+    (seconds_past_grace / SECONDS_PER_DAY).min(u16::MAX as i64) as u16
+}
+
+/// Canonical late fine: daily_fine_bps of the installment per day late, capped at
+/// cap_bps of it but never below min_cap (itself bounded by the installment)
+pub fn compute_fine(monthly_installment: u64, days_late: u16, daily_fine_bps: u16, cap_bps: u16, min_cap: u64) -> u64 {
+    let accrued = (monthly_installment as u128) * (daily_fine_bps as u128) * (days_late as u128) / 10000;
+    let cap = ((monthly_installment as u128) * (cap_bps as u128) / 10000)
+        .max(min_cap.min(monthly_installment) as u128);
+    accrued.min(cap) as u64
+}
+
 // ============================================================================
 // STATE STRUCTS
 // ============================================================================
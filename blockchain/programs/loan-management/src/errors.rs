@@ -23,7 +23,7 @@ pub enum LoanError {
     #[msg("Invalid tenure")]
     InvalidTenure,
 
-    #[msg("User already has an active loan")]
+    #[msg("User already holds the maximum number of active loans")]
     ActiveLoanExists,
 
     #[msg("Loan not found")]
@@ -76,4 +76,190 @@ pub enum LoanError {
 
     #[msg("Income too low")]
     IncomeTooLow,
+
+    #[msg("Invalid configuration value")]
+    InvalidConfigValue,
+
+    #[msg("Note too long")]
+    NoteTooLong,
+
+    #[msg("Installment does not cover the period's interest")]
+    NegativeAmortization,
+
+    #[msg("Invalid loan status transition")]
+    InvalidStatusTransition,
+
+    #[msg("Payment too soon after the previous payment")]
+    PaymentTooFrequent,
+
+    #[msg("Amortization schedule is inconsistent")]
+    AmortizationInconsistent,
+
+    #[msg("Recovery exceeds the defaulted balance")]
+    RecoveryExceedsBalance,
+
+    #[msg("Profile is stale; update it before taking a loan")]
+    ProfileStale,
+
+    #[msg("Adjustment reason must be between 1 and 200 characters")]
+    InvalidAdjustmentReason,
+
+    #[msg("Smart-wallet borrowers are not enabled")]
+    WalletBorrowersDisabled,
+
+    #[msg("Authority is not a PDA of the approved wallet program")]
+    WalletAuthorityMismatch,
+
+    #[msg("Loan insurance is not offered")]
+    InsuranceUnavailable,
+
+    #[msg("Loan is not insured or its insurance was already claimed")]
+    InsuranceNotClaimable,
+
+    #[msg("Insurance pool cannot cover the claim")]
+    InsufficientInsurancePool,
+
+    #[msg("Configured rate oracle account is missing or does not match")]
+    RateOracleMismatch,
+
+    #[msg("Financial education already recorded for this user")]
+    EducationAlreadyRecorded,
+
+    #[msg("Loan has neither passed maturity plus the default grace period nor missed enough installments")]
+    MaturityGraceNotElapsed,
+
+    #[msg("Loan schedule exceeds the borrower liability cap")]
+    LiabilityCapExceeded,
+
+    #[msg("Monthly installment is below the configured minimum")]
+    InstallmentTooSmall,
+
+    #[msg("Loan is not overdue long enough to be non-performing")]
+    NotNonPerforming,
+
+    #[msg("Loan is already marked non-performing")]
+    AlreadyNonPerforming,
+
+    #[msg("Loan has recorded payments and cannot be cancelled")]
+    CannotCancelRepaidLoan,
+
+    #[msg("Installment would push debt-to-income above the configured limit")]
+    DtiTooHigh,
+
+    #[msg("Too many income changes in the current window")]
+    TooManyIncomeChanges,
+
+    #[msg("Account has not existed long enough to borrow")]
+    AccountTooNew,
+
+    #[msg("Installment is paid or still within its grace period")]
+    InstallmentNotMissed,
+
+    #[msg("Installment has already been counted as missed")]
+    InstallmentAlreadyMissed,
+
+    #[msg("Installment is being paid in parts; finish it with record_partial_payment")]
+    PartialPaymentInProgress,
+
+    #[msg("Only completed or cancelled loans can be closed")]
+    LoanNotTerminal,
+
+    #[msg("Loan is still within the data-retention period")]
+    RetentionNotElapsed,
+
+    #[msg("Record does not belong to this loan or its rent payer does not match")]
+    InvalidCloseRecord,
+
+    #[msg("Bring overdue installments current before prepaying the loan")]
+    PrepaymentWhileOverdue,
+
+    #[msg("Program has reached its registration cap")]
+    UserCapReached,
+
+    #[msg("Program state is not in a layout this migration upgrades from")]
+    NothingToMigrate,
+
+    #[msg("Borrowers with this employment type are not eligible for loans")]
+    EmploymentTypeNotEligible,
+
+    #[msg("Too many loans originated in this slot; retry in a later slot")]
+    RateLimited,
+
+    #[msg("Collateral must have a non-zero declared value")]
+    InvalidCollateralValue,
+
+    #[msg("Collateral can only be released from a completed or cancelled loan while still locked")]
+    CollateralNotReleasable,
+
+    #[msg("Secured loans must be defaulted together with their collateral account")]
+    CollateralAccountMismatch,
+
+    #[msg("A borrower cannot guarantee their own loan")]
+    GuarantorIsBorrower,
+
+    #[msg("Guaranteed amount must be non-zero and no more than the loan total")]
+    InvalidGuaranteedAmount,
+
+    #[msg("Guaranteed loans must be defaulted together with their guarantor accounts")]
+    GuarantorAccountMismatch,
+
+    #[msg("Borrower must have a risk profile before a loan can be created")]
+    RiskProfileRequired,
+
+    #[msg("Loan has reached the restructuring limit")]
+    RestructureLimitReached,
+
+    #[msg("Restructuring must change the tenure or the interest rate")]
+    NothingToRestructure,
+
+    #[msg("Only completed or defaulted loans can be closed")]
+    LoanNotClosable,
+
+    #[msg("Reclaimed rent must go back to the account that paid it")]
+    InvalidCloseRecipient,
+
+    #[msg("Fines are settled on-chain; the treasury account is required")]
+    TreasuryRequired,
+
+    #[msg("Treasury holds less than the requested withdrawal")]
+    InsufficientTreasuryFunds,
+
+    #[msg("Reversing a payment on a completed loan requires reopening it")]
+    ReopenRequired,
+
+    #[msg("Only the most recent installment payment can be reversed")]
+    NotLatestPayment,
+
+    #[msg("Borrower has not completed enough loans in good standing for a credit line")]
+    CreditLineNotEligible,
+
+    #[msg("Draw exceeds the credit line's available limit")]
+    CreditLimitExceeded,
+
+    #[msg("Stepped loans must be paid with their step schedule account, and level loans without one")]
+    StepScheduleMismatch,
+
+    #[msg("Step schedule needs one positive multiplier per installment")]
+    InvalidStepSchedule,
+
+    #[msg("A step schedule can only be attached to an active loan with no payments")]
+    StepScheduleNotAllowed,
+
+    #[msg("Loan start date is outside the allowed window around origination")]
+    InvalidStartTimestamp,
+
+    #[msg("Guarantor already backs the maximum number of active loans")]
+    GuaranteeLimitReached,
+
+    #[msg("total_loans can only be raised; lowering it would reuse existing loan ids")]
+    LoanCounterDecrease,
+
+    #[msg("New loans are on hold until the post-unpause cooldown ends")]
+    PostUnpauseCooldown,
+
+    #[msg("Installments were paid out of order; settle the earlier ones before restructuring")]
+    PaymentsOutOfOrder,
+
+    #[msg("Release the loan's collateral before closing it")]
+    CollateralStillLocked,
 }
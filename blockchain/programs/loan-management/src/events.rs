@@ -11,6 +11,17 @@ pub struct UserRegistered {
     pub timestamp: i64,
 }
 
+/// Event emitted when a borrower updates their income or employment type
+#[event]
+pub struct UserProfileUpdated {
+    pub user: Pubkey,
+    pub old_income: u64,
+    pub new_income: u64,
+    pub old_employment: EmploymentType,
+    pub new_employment: EmploymentType,
+    pub timestamp: i64,
+}
+
 /// Event emitted when a loan is created
 #[event]
 pub struct LoanCreated {
@@ -23,6 +34,20 @@ pub struct LoanCreated {
     pub total_amount: u64,
     pub start_timestamp: i64,
     pub end_timestamp: i64,
+    pub insurance_premium: u64,
+    pub rate_discount_bps: u16,
+}
+
+/// Event emitted when an origination fee is withheld from a new loan's disbursement
+#[event]
+pub struct FeeCollected {
+    pub loan_id: u64,
+    pub user: Pubkey,
+    pub principal_amount: u64,
+    pub fee_amount: u64,
+    pub disbursed_amount: u64,
+    pub total_fees_collected: u64,
+    pub timestamp: i64,
 }
 
 /// Event emitted when a payment is recorded
@@ -33,11 +58,26 @@ pub struct PaymentRecorded {
     pub installment_number: u8,
     pub amount: u64,
     pub fine_amount: u64,
+    pub servicing_fee: u64,
     pub payment_timestamp: i64,
     pub on_time: bool,
     pub days_late: u16,
 }
 
+/// Event emitted when an admin reverses a payment entered in error
+#[event]
+pub struct PaymentReversed {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub installment_number: u8,
+    pub amount: u64,
+    pub fine_amount: u64,
+    pub balance_restored: u64,
+    pub reopened: bool,
+    pub reversed_by: Pubkey,
+    pub timestamp: i64,
+}
+
 /// Event emitted when risk score is updated
 #[event]
 pub struct RiskScoreUpdated {
@@ -49,6 +89,27 @@ pub struct RiskScoreUpdated {
     pub timestamp: i64,
 }
 
+/// Event emitted when a recommendation is refreshed without rescoring
+#[event]
+pub struct RecommendationRefreshed {
+    pub user: Pubkey,
+    pub old_recommended_max_loan: u64,
+    pub new_recommended_max_loan: u64,
+    pub monthly_income: u64,
+    pub risk_level: RiskLevel,
+    pub timestamp: i64,
+}
+
+/// Event emitted when income below the floor zeroes a borrower's recommendation
+#[event]
+pub struct RecommendationZeroed {
+    pub user: Pubkey,
+    pub monthly_income: u64,
+    pub min_income_for_recommendation: u64,
+    pub risk_level: RiskLevel,
+    pub timestamp: i64,
+}
+
 /// Event emitted when a loan is marked as defaulted
 #[event]
 pub struct LoanDefaulted {
@@ -57,6 +118,21 @@ pub struct LoanDefaulted {
     pub outstanding_balance: u64,
     pub total_fines: u64,
     pub defaulted_timestamp: i64,
+    pub reason: DefaultReason,
+    /// Completion bonus taken back because the default followed a recent completion
+    pub bonus_clawed_back: u16,
+}
+
+/// Event emitted when a settled installment is booked as principal and interest
+#[event]
+pub struct PaymentSplit {
+    pub loan: Pubkey,
+    pub installment_number: u8,
+    pub principal_portion: u64,
+    pub interest_portion: u64,
+    pub principal_repaid: u64,
+    pub interest_repaid: u64,
+    pub timestamp: i64,
 }
 
 /// Event emitted when a loan is completed
@@ -65,9 +141,36 @@ pub struct LoanCompleted {
     pub loan_id: u64,
     pub user: Pubkey,
     pub total_repaid: u64,
+    /// Residual balance written off as rounding dust
+    pub dust_absorbed: u64,
     pub completed_timestamp: i64,
 }
 
+/// Event emitted when an unpaid installment past its grace period is counted as missed
+#[event]
+pub struct InstallmentMissed {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub installment_number: u8,
+    pub missed_payments: u16,
+    pub credit_score: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a never-serviced loan is voided
+#[event]
+pub struct LoanCancelled {
+    pub loan_id: u64,
+    pub user: Pubkey,
+    pub principal_amount: u64,
+    /// Origination fee owed back to the borrower; zero outside fee_refund_window_days
+    pub fee_refunded: u64,
+    /// Insurance premium returned from the pool; a never-serviced loan was never at risk
+    pub insurance_refunded: u64,
+    pub cancelled_by: Pubkey,
+    pub cancelled_timestamp: i64,
+}
+
 /// Event emitted when a fine is waived
 #[event]
 pub struct FineWaived {
@@ -78,3 +181,422 @@ pub struct FineWaived {
     pub waived_by: Pubkey,
     pub timestamp: i64,
 }
+
+/// Event emitted when an income update pushes a borrower past the DTI limit
+#[event]
+pub struct DtiBreach {
+    pub user: Pubkey,
+    pub monthly_income: u64,
+    pub monthly_obligations: u64,
+    pub dti_bps: u64,
+    pub max_dti_bps: u16,
+    pub risk_level: RiskLevel,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the circuit breaker pauses the program
+#[event]
+pub struct AutoPaused {
+    pub window_start: i64,
+    pub loans_in_window: u32,
+    pub volume_in_window: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority pauses or resumes the program
+#[event]
+pub struct ProgramPauseToggled {
+    pub paused: bool,
+    /// When origination reopens after a resume; 0 when pausing
+    pub cooldown_ends_at: i64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the late-fee grace period or daily rate changes
+#[event]
+pub struct FineParamsUpdated {
+    pub grace_period_days: u8,
+    pub daily_fine_bps: u16,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the set of employment types barred from borrowing changes
+#[event]
+pub struct EmploymentBlocklistUpdated {
+    pub old_mask: u8,
+    pub new_mask: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority changes the registration cap
+#[event]
+pub struct MaxUsersUpdated {
+    pub old_max_users: u64,
+    pub new_max_users: u64,
+    pub total_users: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority changes how many loans a borrower may hold at once
+#[event]
+pub struct MaxActiveLoansUpdated {
+    pub old_max_active_loans: u8,
+    pub new_max_active_loans: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an income change past the window limit is let through for review
+#[event]
+pub struct IncomeChangeThrottled {
+    pub user: Pubkey,
+    pub changes_in_window: u8,
+    pub max_changes_per_window: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority proposes a successor
+#[event]
+pub struct AuthorityTransferProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the proposed successor accepts and becomes the authority
+#[event]
+pub struct AuthorityTransferred {
+    pub old: Pubkey,
+    pub new: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a terminal loan past retention is closed and its rent refunded
+#[event]
+pub struct TerminalLoanClosed {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub status: LoanStatus,
+    pub rent_payer: Pubkey,
+    pub records_closed: u16,
+    pub closed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when funds are recovered on a defaulted loan
+#[event]
+pub struct RecoveryRecorded {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub recovered_amount: u64,
+    pub loan_total_recovered: u64,
+    pub program_total_recovered: u64,
+    pub credit_score: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority corrects program counters
+#[event]
+pub struct ProgramCountersAdjusted {
+    pub authority: Pubkey,
+    pub total_users_before: u64,
+    pub total_users_after: u64,
+    pub total_loans_before: u64,
+    pub total_loans_after: u64,
+    pub total_volume_before: u64,
+    pub total_volume_after: u64,
+    pub total_recovered_before: u64,
+    pub total_recovered_after: u64,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the insurance pool covers a defaulted loan
+#[event]
+pub struct InsuranceClaimed {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub claimed_amount: u64,
+    pub remaining_pool: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the reminder crank reaches a new ladder stage for an installment
+#[event]
+pub struct PaymentReminder {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub installment_number: u8,
+    /// 1-based position in the reminder ladder
+    pub stage: u8,
+    pub due_timestamp: i64,
+    pub amount_due: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the feeder publishes a new oracle rate
+#[event]
+pub struct OracleRateUpdated {
+    pub oracle: Pubkey,
+    pub previous_rate_bps: u16,
+    pub current_rate_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a borrower's financial education is verified
+#[event]
+pub struct EducationCompleted {
+    pub user: Pubkey,
+    pub score_boost: u16,
+    pub new_credit_score: u16,
+    pub verified_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a joint profile's co-authority is set or removed
+#[event]
+pub struct CoAuthorityUpdated {
+    pub user: Pubkey,
+    pub co_authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a borrower changes how they want to be notified
+#[event]
+pub struct NotificationPreferencesUpdated {
+    pub user: Pubkey,
+    pub contact_hash: [u8; 32],
+    pub notification_opt_in: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an overdue loan is classified as non-performing
+#[event]
+pub struct LoanNonPerforming {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub days_past_due: i64,
+    pub credit_score: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a non-performing loan is brought back within npl_days
+#[event]
+pub struct LoanCured {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub days_past_due: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted for each part payment toward an installment
+#[event]
+pub struct PartialPaymentRecorded {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub installment_number: u8,
+    pub amount: u64,
+    pub amount_paid: u64,
+    pub total_due: u64,
+    pub completed: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a borrower clears a loan early in one payment
+#[event]
+pub struct LoanPrepaid {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub outstanding_before: u64,
+    pub rebate: u64,
+    pub installments_remaining: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when collateral is locked against a loan
+#[event]
+pub struct CollateralLocked {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub collateral_type: CollateralType,
+    pub declared_value: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when collateral is handed back after the loan completes
+#[event]
+pub struct CollateralReleased {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub collateral_type: CollateralType,
+    pub declared_value: u64,
+    pub released_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a secured loan defaults and its collateral is seized
+#[event]
+pub struct CollateralSeized {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub collateral_type: CollateralType,
+    pub declared_value: u64,
+    pub outstanding_balance: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a registered user co-signs a loan
+#[event]
+pub struct GuarantorAdded {
+    pub loan: Pubkey,
+    pub borrower: Pubkey,
+    pub guarantor: Pubkey,
+    pub guaranteed_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a guaranteed loan defaults and the guarantor becomes liable
+#[event]
+pub struct GuarantorLiabilityTriggered {
+    pub loan: Pubkey,
+    pub borrower: Pubkey,
+    pub guarantor: Pubkey,
+    pub guaranteed_amount: u64,
+    /// Portion of the outstanding balance the guarantor answers for
+    pub liability: u64,
+    pub score_penalty: u16,
+    pub new_credit_score: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a payment clears an installment's fine but leaves the installment due
+#[event]
+pub struct FineOnlyPayment {
+    pub loan: Pubkey,
+    pub user: Pubkey,
+    pub installment_number: u8,
+    pub fine_paid: u64,
+    /// Scheduled installment amount still owed
+    pub installment_remaining: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an active loan is re-amortized over a new schedule
+#[event]
+pub struct LoanRestructured {
+    pub loan_id: u64,
+    pub user: Pubkey,
+    pub old_installment: u64,
+    pub new_installment: u64,
+    pub old_tenure: u8,
+    pub new_tenure: u8,
+    pub old_interest_rate: u16,
+    pub new_interest_rate: u16,
+    /// Outstanding balance carried into the new schedule as its principal
+    pub restructured_principal: u64,
+    /// What happened to the fines accrued on the old schedule, and how much they came to
+    pub fine_handling: RestructureFineHandling,
+    pub fines_handled: u64,
+    pub restructure_count: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an admin closes a completed or defaulted loan to reclaim its rent
+#[event]
+pub struct LoanClosed {
+    pub loan: Pubkey,
+    pub loan_id: u64,
+    pub user: Pubkey,
+    pub status: LoanStatus,
+    pub rent_payer: Pubkey,
+    pub records_closed: u16,
+    pub lamports_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a fine or servicing fee is transferred into the treasury
+#[event]
+pub struct TreasuryDeposited {
+    pub loan: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority withdraws from the treasury
+#[event]
+pub struct TreasuryWithdrawn {
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a repeat borrower is approved for a credit line
+#[event]
+pub struct CreditLineOpened {
+    pub user: Pubkey,
+    pub credit_line: Pubkey,
+    pub limit: u64,
+    pub interest_rate: u16,
+    pub completed_loans: u16,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a borrower takes a loan against their credit line
+#[event]
+pub struct CreditLineDrawn {
+    pub user: Pubkey,
+    pub credit_line: Pubkey,
+    pub loan: Pubkey,
+    pub loan_id: u64,
+    pub amount: u64,
+    pub available: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a loan is moved onto a stepped repayment plan
+#[event]
+pub struct StepScheduleCreated {
+    pub loan: Pubkey,
+    pub step_schedule: Pubkey,
+    pub first_installment: u64,
+    pub final_installment: u64,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a servicing note is attached to a loan
+#[event]
+pub struct LoanNoteAdded {
+    pub loan: Pubkey,
+    pub note: Pubkey,
+    pub note_index: u16,
+    pub author: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Audit record emitted by every admin-gated mutation
+#[event]
+pub struct AdminAction {
+    pub admin: Pubkey,
+    pub action_code: AdminActionCode,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emit the audit record; every admin-gated handler goes through here so none is missed
+pub(crate) fn emit_admin_action(admin: Pubkey, action_code: AdminActionCode, target: Pubkey, timestamp: i64) {
+    emit!(AdminAction {
+        admin,
+        action_code,
+        target,
+        timestamp,
+    });
+}
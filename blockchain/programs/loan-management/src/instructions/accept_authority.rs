@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::AuthorityTransferred;
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.pending_authority == new_authority.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    let old = program_state.authority;
+    program_state.authority = ctx.accounts.new_authority.key();
+    program_state.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferred {
+        old,
+        new: program_state.authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Authority transferred: {} -> {}", old, program_state.authority);
+
+    Ok(())
+}
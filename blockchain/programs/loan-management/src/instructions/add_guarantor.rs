@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::GuarantorAdded;
+
+#[derive(Accounts)]
+pub struct AddGuarantor<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        init,
+        payer = guarantor,
+        space = Guarantor::LEN,
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump
+    )]
+    pub guarantor_account: Account<'info, Guarantor>,
+
+    /// The guarantor must be a registered user in their own right
+    #[account(
+        mut,
+        seeds = [b"user-profile", guarantor.key().as_ref()],
+        bump = guarantor_profile.bump
+    )]
+    pub guarantor_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// The borrower or the program authority, so nobody can attach themselves to a loan uninvited
+    #[account(
+        constraint = approver.key() == loan.user
+            || approver.key() == program_state.authority @ LoanError::Unauthorized
+    )]
+    pub approver: Signer<'info>,
+
+    /// Signs to consent to the guarantee and funds its rent
+    #[account(mut)]
+    pub guarantor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddGuarantor>, guaranteed_amount: u64) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let guarantor_account = &mut ctx.accounts.guarantor_account;
+    let guarantor = ctx.accounts.guarantor.key();
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(guarantor != loan.user, LoanError::GuarantorIsBorrower);
+    require!(
+        guaranteed_amount > 0 && guaranteed_amount <= loan.total_amount,
+        LoanError::InvalidGuaranteedAmount
+    );
+    require!(
+        ctx.accounts.guarantor_profile.guaranteed_active_loans < ctx.accounts.program_state.max_guarantees,
+        LoanError::GuaranteeLimitReached
+    );
+
+    guarantor_account.loan = loan.key();
+    guarantor_account.guarantor = guarantor;
+    guarantor_account.guarantor_profile = ctx.accounts.guarantor_profile.key();
+    guarantor_account.guaranteed_amount = guaranteed_amount;
+    guarantor_account.monthly_obligation = ((loan.monthly_obligation as u128)
+        * (guaranteed_amount as u128) / (loan.total_amount as u128)) as u64;
+    guarantor_account.added_at = clock.unix_timestamp;
+    guarantor_account.bump = ctx.bumps.guarantor_account;
+
+    loan.has_guarantor = true;
+    ctx.accounts.guarantor_profile.take_on_guarantee(guarantor_account)?;
+
+    emit!(GuarantorAdded {
+        loan: loan.key(),
+        borrower: loan.user,
+        guarantor,
+        guaranteed_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Guarantor {} added to loan {} for {}", guarantor, loan.loan_id, guaranteed_amount);
+
+    Ok(())
+}
+
+/// Pair a loan's guarantor with their profile when the loan has one; shared by every path that takes a loan out of Active
+pub(crate) fn guarantor_accounts<'a>(
+    loan: &Loan,
+    guarantor: Option<&'a Guarantor>,
+    guarantor_profile: Option<&'a mut UserProfile>,
+) -> Result<Option<(&'a Guarantor, &'a mut UserProfile)>> {
+    require!(loan.has_guarantor == guarantor.is_some(), LoanError::GuarantorAccountMismatch);
+    let Some(guarantor) = guarantor else {
+        return Ok(None);
+    };
+    let guarantor_profile = guarantor_profile
+        .filter(|profile| profile.authority == guarantor.guarantor)
+        .ok_or(LoanError::GuarantorAccountMismatch)?;
+    Ok(Some((guarantor, guarantor_profile)))
+}
+
+/// The loan left Active, so its guarantee no longer counts against the guarantor's capacity
+pub(crate) fn release_guarantor(
+    loan: &Loan,
+    guarantor: Option<&Guarantor>,
+    guarantor_profile: Option<&mut UserProfile>,
+) -> Result<()> {
+    if let Some((guarantor, guarantor_profile)) = guarantor_accounts(loan, guarantor, guarantor_profile)? {
+        guarantor_profile.release_guarantee(guarantor);
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, LoanNoteAdded};
+
+#[derive(Accounts)]
+pub struct AddLoanNote<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = LoanNote::LEN,
+        seeds = [
+            b"loan-note",
+            loan.key().as_ref(),
+            &loan.note_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub loan_note: Account<'info, LoanNote>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddLoanNote>, text: String) -> Result<()> {
+    require!(text.len() <= LoanNote::MAX_TEXT_LEN, LoanError::NoteTooLong);
+
+    let loan = &mut ctx.accounts.loan;
+    let loan_note = &mut ctx.accounts.loan_note;
+    let clock = Clock::get()?;
+
+    loan_note.loan = loan.key();
+    loan_note.author = ctx.accounts.admin.key();
+    loan_note.note_index = loan.note_count;
+    loan_note.timestamp = clock.unix_timestamp;
+    loan_note.text = text;
+    loan_note.bump = ctx.bumps.loan_note;
+
+    loan.note_count = loan.note_count.checked_add(1)
+        .ok_or(LoanError::MathOverflow)?;
+
+    emit!(LoanNoteAdded {
+        loan: loan.key(),
+        note: loan_note.key(),
+        note_index: loan_note.note_index,
+        author: loan_note.author,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::AddLoanNote, loan.key(), clock.unix_timestamp);
+
+    msg!("Note {} added to loan {}", loan_note.note_index, loan.loan_id);
+
+    Ok(())
+}
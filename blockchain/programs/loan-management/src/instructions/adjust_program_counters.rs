@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, ProgramCountersAdjusted};
+
+pub const MAX_ADJUSTMENT_REASON_LEN: usize = 200;
+
+/// Corrected counter values computed off-chain; `None` leaves a counter untouched
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct CounterAdjustment {
+    pub total_users: Option<u64>,
+    pub total_loans: Option<u64>,
+    pub total_volume: Option<u64>,
+    pub total_recovered: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct AdjustProgramCounters<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<AdjustProgramCounters>,
+    adjustment: CounterAdjustment,
+    reason: String,
+) -> Result<()> {
+    require!(!reason.is_empty() && reason.len() <= MAX_ADJUSTMENT_REASON_LEN, LoanError::InvalidAdjustmentReason);
+
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    let before = (
+        program_state.total_users,
+        program_state.total_loans,
+        program_state.total_volume,
+        program_state.total_recovered,
+    );
+
+    if let Some(total_users) = adjustment.total_users {
+        program_state.total_users = total_users;
+    }
+    if let Some(total_loans) = adjustment.total_loans {
+        // total_loans seeds the next loan PDA, so lowering it below an existing
+        // loan id would make the next create_loan collide with that account
+        require!(total_loans >= program_state.total_loans, LoanError::LoanCounterDecrease);
+        program_state.total_loans = total_loans;
+    }
+    if let Some(total_volume) = adjustment.total_volume {
+        program_state.total_volume = total_volume;
+    }
+    if let Some(total_recovered) = adjustment.total_recovered {
+        program_state.total_recovered = total_recovered;
+    }
+
+    emit!(ProgramCountersAdjusted {
+        authority: ctx.accounts.authority.key(),
+        total_users_before: before.0,
+        total_users_after: program_state.total_users,
+        total_loans_before: before.1,
+        total_loans_after: program_state.total_loans,
+        total_volume_before: before.2,
+        total_volume_after: program_state.total_volume,
+        total_recovered_before: before.3,
+        total_recovered_after: program_state.total_recovered,
+        reason,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.authority.key(), AdminActionCode::AdjustProgramCounters, program_state.key(), clock.unix_timestamp);
+
+    msg!("Program counters adjusted by {}", ctx.accounts.authority.key());
+
+    Ok(())
+}
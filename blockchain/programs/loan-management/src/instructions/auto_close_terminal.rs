@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::TerminalLoanClosed;
+use crate::instructions::release_collateral::require_collateral_cleared;
+
+#[derive(Accounts)]
+pub struct AutoCloseTerminal<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump,
+        has_one = rent_payer,
+        close = rent_payer
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan is secured
+    #[account(
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump = collateral.bump,
+        has_one = loan
+    )]
+    pub collateral: Option<Account<'info, Collateral>>,
+
+    /// CHECK: Receives the loan's rent; must match loan.rent_payer
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    /// Anyone can crank the close once retention has passed
+    pub caller: Signer<'info>,
+}
+
+/// Remaining accounts are (record, rent payer) pairs for the loan's payment
+/// records and part-payment trackers; each record's rent goes back to its own payer
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, AutoCloseTerminal<'info>>) -> Result<()> {
+    let loan = &ctx.accounts.loan;
+    let program_state = &ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.terminal_timestamp().is_some(), LoanError::LoanNotTerminal);
+    require!(
+        loan.is_closeable(clock.unix_timestamp, program_state.retention_period_seconds),
+        LoanError::RetentionNotElapsed
+    );
+    require_collateral_cleared(loan, ctx.accounts.collateral.as_deref())?;
+
+    // A trailing record without its payer is left in the remainder
+    let pairs = ctx.remaining_accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), LoanError::InvalidCloseRecord);
+
+    let mut records_closed: u16 = 0;
+    for pair in pairs {
+        let (record_info, payer_info) = (&pair[0], &pair[1]);
+        let discriminator: [u8; 8] = {
+            let data = record_info.try_borrow_data()?;
+            data.get(..8)
+                .and_then(|d| d.try_into().ok())
+                .ok_or(LoanError::InvalidCloseRecord)?
+        };
+
+        if discriminator == PaymentRecord::DISCRIMINATOR {
+            let record: Account<'info, PaymentRecord> = Account::try_from(record_info)?;
+            require!(
+                record.loan == loan.key() && record.rent_payer == payer_info.key(),
+                LoanError::InvalidCloseRecord
+            );
+            record.close(payer_info.clone())?;
+        } else if discriminator == InstallmentState::DISCRIMINATOR {
+            let tracker: Account<'info, InstallmentState> = Account::try_from(record_info)?;
+            require!(
+                tracker.loan == loan.key() && tracker.rent_payer == payer_info.key(),
+                LoanError::InvalidCloseRecord
+            );
+            tracker.close(payer_info.clone())?;
+        } else {
+            return err!(LoanError::InvalidCloseRecord);
+        }
+
+        records_closed = records_closed.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+    }
+
+    emit!(TerminalLoanClosed {
+        loan: loan.key(),
+        user: loan.user,
+        status: loan.status.clone(),
+        rent_payer: loan.rent_payer,
+        records_closed,
+        closed_by: ctx.accounts.caller.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Terminal loan {} closed with {} records", loan.loan_id, records_closed);
+
+    Ok(())
+}
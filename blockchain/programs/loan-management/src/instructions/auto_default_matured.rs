@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::instructions::mark_loan_defaulted::{apply_default, trigger_guarantor};
+
+#[derive(Accounts)]
+pub struct AutoDefaultMatured<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan is secured
+    #[account(
+        mut,
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump = collateral.bump
+    )]
+    pub collateral: Option<Account<'info, Collateral>>,
+
+    /// Required when the loan has a guarantor
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+}
+
+pub fn handler(ctx: Context<AutoDefaultMatured>) -> Result<()> {
+    let clock = Clock::get()?;
+    let loan = &ctx.accounts.loan;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(loan.outstanding_balance > 0, LoanError::LoanAlreadyCompleted);
+
+    // Permissionless crank: the clock and the recorded misses alone decide, so anyone can trigger it
+    let program_state = &ctx.accounts.program_state;
+    let grace = program_state.grace_after_maturity_days as i64 * SECONDS_PER_DAY;
+    let missed_threshold = program_state.missed_installments_to_default as u32;
+    let reason = if clock.unix_timestamp >= loan.end_timestamp + grace {
+        DefaultReason::MaturityBreach
+    } else if missed_threshold > 0 && loan.missed_installments.count_ones() >= missed_threshold {
+        DefaultReason::MissedInstallments
+    } else {
+        return err!(LoanError::MaturityGraceNotElapsed);
+    };
+
+    apply_default(
+        &mut ctx.accounts.loan,
+        &mut ctx.accounts.user_profile,
+        &ctx.accounts.program_state,
+        ctx.accounts.collateral.as_deref_mut(),
+        clock.unix_timestamp,
+        reason,
+    )?;
+    ctx.accounts.program_state.sync_loan_segment(
+        &mut ctx.accounts.loan,
+        &ctx.accounts.user_profile.risk_level,
+    );
+    trigger_guarantor(
+        &ctx.accounts.loan,
+        ctx.accounts.guarantor.as_deref(),
+        ctx.accounts.guarantor_profile.as_deref_mut(),
+        &ctx.accounts.program_state,
+        clock.unix_timestamp,
+    )
+}
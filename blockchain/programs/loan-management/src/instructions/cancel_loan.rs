@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, LoanCancelled};
+use crate::instructions::add_guarantor::release_guarantor;
+
+#[derive(Accounts)]
+pub struct CancelLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", user_profile.authority.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan has a guarantor, so the guarantee stops counting against them
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelLoan>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    // Only a loan that was never serviced can be voided as a mistake
+    require!(
+        loan.total_repaid == 0 && loan.installments_paid == 0,
+        LoanError::CannotCancelRepaidLoan
+    );
+
+    loan.validate_status_timestamps()?;
+    loan.status = LoanStatus::Cancelled;
+    loan.cancelled_timestamp = Some(clock.unix_timestamp);
+    loan.validate_status_timestamps()?;
+
+    // Undo the origination's effect on the borrower without any score impact
+    user_profile.active_loans = user_profile.active_loans.saturating_sub(1);
+    user_profile.monthly_obligations = user_profile.monthly_obligations.saturating_sub(loan.monthly_obligation);
+    user_profile.total_borrowed = user_profile.total_borrowed.saturating_sub(loan.principal_amount);
+    user_profile.last_updated = clock.unix_timestamp;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    // A quick cancellation owes the withheld fee back; the refund is paid out off-chain,
+    // like the disbursement it was withheld from
+    let refund_window = program_state.fee_refund_window_days as i64 * SECONDS_PER_DAY;
+    let fee_refunded = if clock.unix_timestamp - loan.created_timestamp < refund_window {
+        loan.origination_fee
+    } else {
+        0
+    };
+    program_state.total_fees_collected = program_state.total_fees_collected.saturating_sub(fee_refunded);
+
+    // The premium always comes back, whatever the timing, since nothing was ever insured
+    let insurance_refunded = loan.insurance_premium.min(program_state.insurance_pool);
+    program_state.insurance_pool -= insurance_refunded;
+    release_guarantor(loan, ctx.accounts.guarantor.as_deref(), ctx.accounts.guarantor_profile.as_deref_mut())?;
+
+    emit!(LoanCancelled {
+        loan_id: loan.loan_id,
+        user: loan.user,
+        principal_amount: loan.principal_amount,
+        fee_refunded,
+        insurance_refunded,
+        cancelled_by: ctx.accounts.admin.key(),
+        cancelled_timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::CancelLoan, loan.key(), clock.unix_timestamp);
+
+    msg!("Loan {} cancelled", loan.loan_id);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, InsuranceClaimed};
+
+#[derive(Accounts)]
+pub struct ClaimInsurance<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ClaimInsurance>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Defaulted, LoanError::InvalidStatusTransition);
+    require!(
+        loan.insurance_premium > 0 && loan.insurance_claimed == 0,
+        LoanError::InsuranceNotClaimable
+    );
+
+    // Cover whatever collections haven't already recovered
+    let claimed_amount = loan.outstanding_balance.saturating_sub(loan.total_recovered);
+    require!(claimed_amount > 0, LoanError::InsuranceNotClaimable);
+    require!(
+        program_state.insurance_pool >= claimed_amount,
+        LoanError::InsufficientInsurancePool
+    );
+
+    program_state.insurance_pool -= claimed_amount;
+    program_state.defaulted_outstanding = program_state.defaulted_outstanding.saturating_sub(claimed_amount);
+    loan.insurance_claimed = claimed_amount;
+
+    emit!(InsuranceClaimed {
+        loan: loan.key(),
+        user: loan.user,
+        claimed_amount,
+        remaining_pool: program_state.insurance_pool,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::ClaimInsurance, loan.key(), clock.unix_timestamp);
+
+    msg!("Insurance paid {} on loan {}", claimed_amount, loan.loan_id);
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, LoanClosed};
+use crate::instructions::release_collateral::require_collateral_cleared;
+
+#[derive(Accounts)]
+pub struct CloseLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump,
+        has_one = rent_payer @ LoanError::InvalidCloseRecipient,
+        close = rent_payer
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan is secured
+    #[account(
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump = collateral.bump,
+        has_one = loan
+    )]
+    pub collateral: Option<Account<'info, Collateral>>,
+
+    /// CHECK: Receives the loan's rent; must match loan.rent_payer
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Remaining accounts are (record, rent payer) pairs for the loan's payment
+/// records and part-payment trackers; each record's rent goes back to its own payer
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CloseLoan<'info>>) -> Result<()> {
+    let loan = &ctx.accounts.loan;
+    let clock = Clock::get()?;
+
+    require!(
+        matches!(loan.status, LoanStatus::Completed | LoanStatus::Defaulted),
+        LoanError::LoanNotClosable
+    );
+    require_collateral_cleared(loan, ctx.accounts.collateral.as_deref())?;
+
+    // Closing a defaulted loan gives up on what's left of it
+    if loan.status == LoanStatus::Defaulted {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.defaulted_outstanding = program_state.defaulted_outstanding.saturating_sub(loan.unrecovered_balance());
+    }
+
+    // A trailing record without its payer is left in the remainder
+    let pairs = ctx.remaining_accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), LoanError::InvalidCloseRecord);
+
+    let mut records_closed: u16 = 0;
+    let mut lamports_reclaimed = loan.to_account_info().lamports();
+    for pair in pairs {
+        let (record_info, payer_info) = (&pair[0], &pair[1]);
+        let discriminator: [u8; 8] = {
+            let data = record_info.try_borrow_data()?;
+            data.get(..8)
+                .and_then(|d| d.try_into().ok())
+                .ok_or(LoanError::InvalidCloseRecord)?
+        };
+        lamports_reclaimed = lamports_reclaimed.checked_add(record_info.lamports())
+            .ok_or(LoanError::MathOverflow)?;
+
+        if discriminator == PaymentRecord::DISCRIMINATOR {
+            let record: Account<'info, PaymentRecord> = Account::try_from(record_info)?;
+            require!(
+                record.loan == loan.key() && record.rent_payer == payer_info.key(),
+                LoanError::InvalidCloseRecord
+            );
+            record.close(payer_info.clone())?;
+        } else if discriminator == InstallmentState::DISCRIMINATOR {
+            let tracker: Account<'info, InstallmentState> = Account::try_from(record_info)?;
+            require!(
+                tracker.loan == loan.key() && tracker.rent_payer == payer_info.key(),
+                LoanError::InvalidCloseRecord
+            );
+            tracker.close(payer_info.clone())?;
+        } else {
+            return err!(LoanError::InvalidCloseRecord);
+        }
+
+        records_closed = records_closed.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+    }
+
+    emit!(LoanClosed {
+        loan: loan.key(),
+        loan_id: loan.loan_id,
+        user: loan.user,
+        status: loan.status.clone(),
+        rent_payer: loan.rent_payer,
+        records_closed,
+        lamports_reclaimed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.admin.key(),
+        AdminActionCode::CloseLoan,
+        loan.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Loan {} closed with {} records, {} lamports reclaimed",
+        loan.loan_id, records_closed, lamports_reclaimed);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::emit_admin_action;
+use crate::instructions::update_risk_score::apply_risk_score;
+
+/// Number of profile factors UserProfile::computed_risk_score weighs
+pub const COMPUTED_RISK_FACTORS: u8 = 7;
+
+#[derive(Accounts)]
+pub struct ComputeRiskScore<'info> {
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = RiskProfile::LEN,
+        seeds = [b"risk-profile", user.key().as_ref()],
+        bump
+    )]
+    pub risk_profile: Account<'info, RiskProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ComputeRiskScore>) -> Result<()> {
+    let clock = Clock::get()?;
+    let risk_score = ctx.accounts.user_profile.computed_risk_score();
+    let default_probability = default_probability_for(risk_score);
+
+    apply_risk_score(
+        &mut ctx.accounts.user_profile,
+        &mut ctx.accounts.risk_profile,
+        &ctx.accounts.program_state,
+        ctx.accounts.user.key(),
+        risk_score,
+        None,
+        default_probability,
+        ctx.bumps.risk_profile,
+        clock.unix_timestamp,
+    )?;
+    ctx.accounts.risk_profile.factors_count = COMPUTED_RISK_FACTORS;
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::ComputeRiskScore, ctx.accounts.user.key(), clock.unix_timestamp);
+
+    msg!("Risk score computed for user: score={}, level={:?}", risk_score, ctx.accounts.risk_profile.risk_level);
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::LoanCreated;
+use crate::events::{emit_admin_action, AutoPaused, FeeCollected, LoanCreated};
 
 #[derive(Accounts)]
 #[instruction(principal_amount: u64)]
@@ -29,10 +29,22 @@ pub struct CreateLoan<'info> {
     #[account(
         mut,
         seeds = [b"program-state"],
-        bump = program_state.bump
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
     )]
     pub program_state: Account<'info, LoanProgramState>,
 
+    /// Required only when a rate oracle is configured
+    pub rate_oracle: Option<Account<'info, RateOracle>>,
+
+    /// CHECK: Borrower's risk-profile PDA, always passed so the default-probability limit
+    /// can't be dodged by leaving it out; empty while the borrower has no profile
+    #[account(
+        seeds = [b"risk-profile", user_authority.key().as_ref()],
+        bump
+    )]
+    pub risk_profile: AccountInfo<'info>,
+
     /// CHECK: User authority
     pub user_authority: AccountInfo<'info>,
 
@@ -48,8 +60,32 @@ pub fn handler(
     interest_rate: u16,
     tenure_months: u8,
     start_timestamp: i64,
+    with_insurance: bool,
+    spread_bps: Option<u16>,
 ) -> Result<()> {
     require!(!ctx.accounts.program_state.paused, LoanError::ProgramPaused);
+    check_risk_profile(&ctx.accounts.risk_profile, &ctx.accounts.program_state)?;
+
+    // Floating-rate mode: the oracle plus spread replaces the requested rate
+    let configured_oracle = ctx.accounts.program_state.rate_oracle;
+    let interest_rate = if configured_oracle != Pubkey::default() {
+        let rate_oracle = ctx.accounts.rate_oracle.as_ref()
+            .filter(|oracle| oracle.key() == configured_oracle)
+            .ok_or(LoanError::RateOracleMismatch)?;
+
+        // A per-loan spread overrides the default, but only within the configured bounds
+        let spread = spread_bps.unwrap_or(ctx.accounts.program_state.oracle_spread_bps);
+        require!(
+            spread >= ctx.accounts.program_state.min_spread_bps
+                && spread <= ctx.accounts.program_state.max_spread_bps,
+            LoanError::InvalidInterestRate
+        );
+        rate_oracle.current_rate_bps
+            .checked_add(spread)
+            .ok_or(LoanError::MathOverflow)?
+    } else {
+        interest_rate
+    };
     
     // Validate inputs
     require!(
@@ -57,51 +93,244 @@ pub fn handler(
         LoanError::InvalidLoanAmount
     ); // 5k to 500k PKR (in lamports equivalent)
     
-    require!(interest_rate > 0 && interest_rate <= 3000, LoanError::InvalidInterestRate); // 0-30%
+    require!(
+        interest_rate > 0 && interest_rate <= LoanProgramState::MAX_INTEREST_RATE,
+        LoanError::InvalidInterestRate
+    ); // 0-30%
+
+    // Risk-based pricing: the borrower's score band bounds the allowed rate
+    let band = ctx.accounts.program_state.rate_band_for(ctx.accounts.user_profile.credit_score);
+    require!(
+        interest_rate >= band.min_rate && interest_rate <= band.max_rate,
+        LoanError::InvalidInterestRate
+    );
     require!(tenure_months >= 3 && tenure_months <= 60, LoanError::InvalidTenure);
-    require!(ctx.accounts.user_profile.active_loans == 0, LoanError::ActiveLoanExists);
+    require!(
+        ctx.accounts.user_profile.active_loans < ctx.accounts.program_state.max_active_loans,
+        LoanError::ActiveLoanExists
+    );
+
+    // Good borrowers get a discount off the offered rate, never below the floor
+    let pricing = &ctx.accounts.program_state;
+    let offered_rate = interest_rate;
+    let interest_rate = if ctx.accounts.user_profile.credit_score > pricing.discount_score_threshold {
+        offered_rate
+            .saturating_sub(pricing.score_discount_bps)
+            .max(pricing.discount_rate_floor.min(offered_rate))
+    } else {
+        offered_rate
+    };
+    let rate_discount_bps = offered_rate - interest_rate;
 
     let user_profile = &mut ctx.accounts.user_profile;
     let loan = &mut ctx.accounts.loan;
     let program_state = &mut ctx.accounts.program_state;
     let clock = Clock::get()?;
 
-    // Calculate loan details
-    let monthly_rate = (interest_rate as f64) / 12.0 / 10000.0;
-    let n = tenure_months as f64;
-    
-    let monthly_installment = if monthly_rate == 0.0 {
-        principal_amount / (tenure_months as u64)
+    require!(
+        clock.unix_timestamp >= program_state.post_unpause_cooldown_ends_at(),
+        LoanError::PostUnpauseCooldown
+    );
+
+    // A start far in the past would make every installment instantly late; far ahead, never due
+    require!(
+        start_timestamp >= clock.unix_timestamp - program_state.max_start_backdate_seconds
+            && start_timestamp <= clock.unix_timestamp + program_state.max_start_lead_seconds,
+        LoanError::InvalidStartTimestamp
+    );
+
+    // Underwrite only on reasonably fresh income data
+    require!(
+        clock.unix_timestamp - user_profile.last_updated <= program_state.max_profile_age_for_loan,
+        LoanError::ProfileStale
+    );
+
+    // Brand-new accounts can't borrow straight away, deterring hit-and-run fraud
+    require!(
+        clock.unix_timestamp - user_profile.registration_timestamp >= program_state.min_account_age_for_loan,
+        LoanError::AccountTooNew
+    );
+
+    // Blocked types keep their profile but can't take on new debt
+    require!(
+        !program_state.is_employment_blocked(&user_profile.employment_type),
+        LoanError::EmploymentTypeNotEligible
+    );
+
+    let schedule = amortize(principal_amount, interest_rate, tenure_months, program_state)?;
+
+    // Capacity reflects every active commitment, not just this installment, including
+    // a weighted share of the loans this borrower guarantees for others
+    let added_obligations = schedule.monthly_installment.saturating_add(user_profile.counted_guarantees(program_state));
+    let dti_bps = user_profile.debt_to_income_bps_with(added_obligations)
+        .ok_or(LoanError::IncomeTooLow)?;
+    require!(dti_bps <= program_state.max_dti_bps as u64, LoanError::DtiTooHigh);
+
+    // Insurance premium is collected upfront rather than financed, so the schedule is unchanged
+    let insurance_premium = if with_insurance {
+        require!(program_state.insurance_premium_bps > 0, LoanError::InsuranceUnavailable);
+        ((principal_amount as u128) * (program_state.insurance_premium_bps as u128) / 10000) as u64
     } else {
-        let numerator = (principal_amount as f64) * monthly_rate * (1.0 + monthly_rate).powf(n);
-        let denominator = (1.0 + monthly_rate).powf(n) - 1.0;
-        (numerator / denominator) as u64
+        0
     };
 
-    let total_amount = monthly_installment
-        .checked_mul(tenure_months as u64)
-        .ok_or(LoanError::MathOverflow)?;
+    originate(
+        loan,
+        user_profile,
+        program_state,
+        ctx.accounts.user_authority.key(),
+        ctx.accounts.admin.key(),
+        ctx.bumps.loan,
+        Origination {
+            principal_amount,
+            interest_rate,
+            tenure_months,
+            start_timestamp,
+            schedule,
+            insurance_premium,
+            rate_discount_bps,
+        },
+        &clock,
+    )?;
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::CreateLoan, loan.key(), clock.unix_timestamp);
 
-    let end_timestamp = start_timestamp + ((tenure_months as i64) * 30 * 24 * 60 * 60);
+    msg!("Loan created: ID={}, Amount={}, Tenure={} months", loan.loan_id, principal_amount, tenure_months);
+
+    Ok(())
+}
+
+/// Terms of a loan about to be originated, already priced and underwritten
+pub(crate) struct Origination {
+    pub principal_amount: u64,
+    pub interest_rate: u16,
+    pub tenure_months: u8,
+    pub start_timestamp: i64,
+    pub schedule: Schedule,
+    pub insurance_premium: u64,
+    pub rate_discount_bps: u16,
+}
+
+/// Enforce require_risk_profile and the default-probability ceiling against the
+/// borrower's risk-profile PDA; shared by direct origination and credit line draws
+pub(crate) fn check_risk_profile(info: &AccountInfo, program_state: &LoanProgramState) -> Result<()> {
+    let risk_profile = if info.data_is_empty() {
+        None
+    } else {
+        Some(RiskProfile::try_deserialize(&mut &info.try_borrow_data()?[..])?)
+    };
+    require!(
+        !program_state.require_risk_profile || risk_profile.is_some(),
+        LoanError::RiskProfileRequired
+    );
+
+    // Without a profile there's no estimate to act on; require_risk_profile closes that gap
+    let max_default_probability = program_state.max_default_probability_for_loan;
+    if let Some(risk_profile) = risk_profile.as_ref() {
+        require!(
+            max_default_probability == 0 || risk_profile.default_probability <= max_default_probability,
+            LoanError::HighRiskUser
+        );
+    }
+    Ok(())
+}
+
+/// Book a new loan against the borrower and program counters; shared by direct
+/// origination and credit line draws, which differ only in how they underwrite
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn originate(
+    loan: &mut Loan,
+    user_profile: &mut UserProfile,
+    program_state: &mut LoanProgramState,
+    user: Pubkey,
+    rent_payer: Pubkey,
+    bump: u8,
+    terms: Origination,
+    clock: &Clock,
+) -> Result<()> {
+    let Origination {
+        principal_amount,
+        interest_rate,
+        tenure_months,
+        start_timestamp,
+        schedule: Schedule { monthly_installment, final_installment, total_amount },
+        insurance_premium,
+        rate_discount_bps,
+    } = terms;
+    // Coarse safety valve against runaway batch scripts, independent of the circuit breaker
+    if program_state.max_loans_per_slot > 0 {
+        if program_state.origination_slot != clock.slot {
+            program_state.origination_slot = clock.slot;
+            program_state.loans_in_origination_slot = 0;
+        }
+        require!(
+            program_state.loans_in_origination_slot < program_state.max_loans_per_slot,
+            LoanError::RateLimited
+        );
+        program_state.loans_in_origination_slot += 1;
+    }
+
+    // The origination fee comes out of the disbursement, so the borrower repays the
+    // schedule on the full principal but receives principal less the fee
+    let origination_fee = ((principal_amount as u128) * (program_state.fee_percentage as u128) / 10000) as u64;
+
+    let end_timestamp = start_timestamp + (tenure_months as i64) * SECONDS_PER_PERIOD;
 
     // Populate loan account
-    loan.user = ctx.accounts.user_authority.key();
+    loan.user = user;
     loan.loan_id = program_state.total_loans;
     loan.principal_amount = principal_amount;
     loan.interest_rate = interest_rate;
     loan.tenure_months = tenure_months;
     loan.monthly_installment = monthly_installment;
+    loan.monthly_obligation = monthly_installment;
+    loan.final_installment = final_installment;
     loan.total_amount = total_amount;
     loan.outstanding_balance = total_amount;
     loan.total_repaid = 0;
     loan.total_fines = 0;
+    loan.pending_fines = 0;
+    loan.carried_fine = 0;
     loan.start_timestamp = start_timestamp;
     loan.end_timestamp = end_timestamp;
     loan.status = LoanStatus::Active;
     loan.created_timestamp = clock.unix_timestamp;
     loan.completed_timestamp = None;
     loan.defaulted_timestamp = None;
-    loan.bump = ctx.bumps.loan;
+    loan.cancelled_timestamp = None;
+    loan.is_secured = false;
+    loan.has_guarantor = false;
+    loan.restructure_count = 0;
+    loan.schedule_offset = 0;
+    loan.note_count = 0;
+    loan.installments_paid = 0;
+    loan.is_first_loan = user_profile.completed_loans == 0 && user_profile.defaulted_loans == 0;
+    loan.last_payment_at = 0;
+    loan.last_payment_installment = 0;
+    loan.total_recovered = 0;
+    loan.insurance_premium = insurance_premium;
+    loan.insurance_claimed = 0;
+    loan.origination_fee = origination_fee;
+    loan.servicing_fee_per_installment = program_state.servicing_fee_per_installment;
+    loan.total_servicing_fees = 0;
+    loan.risk_segment = None;
+    loan.principal_repaid = 0;
+    loan.interest_repaid = 0;
+    loan.is_stepped = false;
+    loan.rate_discount_bps = rate_discount_bps;
+    loan.last_reminder_installment = 0;
+    loan.last_reminder_stage = 0;
+    loan.rent_payer = rent_payer;
+    loan.non_performing = false;
+    loan.missed_installments = 0;
+    loan.paid_installments = 0;
+    loan.reversible_installment = 0;
+    loan.bump = bump;
+
+    // Interest alone must leave the schedule within the liability cap
+    if let Some(cap) = loan.liability_cap(program_state) {
+        require!(total_amount <= cap, LoanError::LiabilityCapExceeded);
+    }
 
     // Update user profile
     user_profile.total_loans = user_profile.total_loans.checked_add(1)
@@ -110,6 +339,8 @@ pub fn handler(
         .ok_or(LoanError::MathOverflow)?;
     user_profile.total_borrowed = user_profile.total_borrowed.checked_add(principal_amount)
         .ok_or(LoanError::MathOverflow)?;
+    user_profile.monthly_obligations = user_profile.monthly_obligations.checked_add(monthly_installment)
+        .ok_or(LoanError::MathOverflow)?;
     user_profile.last_updated = clock.unix_timestamp;
 
     // Update program state
@@ -117,6 +348,41 @@ pub fn handler(
         .ok_or(LoanError::MathOverflow)?;
     program_state.total_volume = program_state.total_volume.checked_add(principal_amount)
         .ok_or(LoanError::MathOverflow)?;
+    program_state.insurance_pool = program_state.insurance_pool.checked_add(insurance_premium)
+        .ok_or(LoanError::MathOverflow)?;
+    program_state.total_fees_collected = program_state.total_fees_collected.checked_add(origination_fee)
+        .ok_or(LoanError::MathOverflow)?;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    // Circuit breaker: this loan goes through, but the program pauses behind it
+    if program_state.anomaly_detection_enabled {
+        if clock.unix_timestamp - program_state.anomaly_window_start >= program_state.anomaly_window_seconds {
+            program_state.anomaly_window_start = clock.unix_timestamp;
+            program_state.anomaly_window_loans = 0;
+            program_state.anomaly_window_volume = 0;
+        }
+
+        program_state.anomaly_window_loans = program_state.anomaly_window_loans.saturating_add(1);
+        program_state.anomaly_window_volume = program_state.anomaly_window_volume.saturating_add(principal_amount);
+
+        let too_many_loans = program_state.anomaly_max_loans > 0
+            && program_state.anomaly_window_loans > program_state.anomaly_max_loans;
+        let too_much_volume = program_state.anomaly_max_volume > 0
+            && program_state.anomaly_window_volume > program_state.anomaly_max_volume;
+
+        if too_many_loans || too_much_volume {
+            program_state.paused = true;
+
+            emit!(AutoPaused {
+                window_start: program_state.anomaly_window_start,
+                loans_in_window: program_state.anomaly_window_loans,
+                volume_in_window: program_state.anomaly_window_volume,
+                timestamp: clock.unix_timestamp,
+            });
+
+            msg!("Anomalous origination detected; program auto-paused");
+        }
+    }
 
     emit!(LoanCreated {
         loan_id: loan.loan_id,
@@ -128,9 +394,95 @@ pub fn handler(
         total_amount,
         start_timestamp,
         end_timestamp,
+        insurance_premium,
+        rate_discount_bps,
     });
 
-    msg!("Loan created: ID={}, Amount={}, Tenure={} months", loan.loan_id, principal_amount, tenure_months);
+    if origination_fee > 0 {
+        emit!(FeeCollected {
+            loan_id: loan.loan_id,
+            user: loan.user,
+            principal_amount,
+            fee_amount: origination_fee,
+            disbursed_amount: principal_amount - origination_fee,
+            total_fees_collected: program_state.total_fees_collected,
+            timestamp: clock.unix_timestamp,
+        });
+    }
 
     Ok(())
 }
+
+/// Installment amounts for a level-payment schedule
+pub(crate) struct Schedule {
+    pub monthly_installment: u64,
+    pub final_installment: u64,
+    pub total_amount: u64,
+}
+
+/// Amortize a principal over a tenure; shared by origination and restructuring
+pub(crate) fn amortize(
+    principal_amount: u64,
+    interest_rate: u16,
+    tenure_months: u8,
+    program_state: &LoanProgramState,
+) -> Result<Schedule> {
+    // Calculate loan details
+    let monthly_rate = (interest_rate as f64) / 12.0 / 10000.0;
+    let n = tenure_months as f64;
+    
+    let exact_installment = if monthly_rate == 0.0 {
+        (principal_amount as f64) / n
+    } else {
+        let numerator = (principal_amount as f64) * monthly_rate * (1.0 + monthly_rate).powf(n);
+        let denominator = (1.0 + monthly_rate).powf(n) - 1.0;
+        numerator / denominator
+    };
+    // Borrower-friendly mode rounds down to whole units instead of truncating to arbitrary dust
+    let rounding_unit = program_state.min_installment_amount;
+    let borrower_friendly = program_state.borrower_friendly_rounding && rounding_unit > 0;
+    let monthly_installment = if borrower_friendly {
+        (exact_installment as u64) / rounding_unit * rounding_unit
+    } else {
+        exact_installment as u64
+    };
+    require!(
+        monthly_installment >= program_state.min_installment_amount,
+        LoanError::InstallmentTooSmall
+    );
+
+    // An installment below the first period's interest would grow the balance
+    let first_period_interest = ((principal_amount as u128) * (interest_rate as u128) / 12 / 10000) as u64;
+    require!(
+        program_state.allow_neg_am || monthly_installment > first_period_interest,
+        LoanError::NegativeAmortization
+    );
+
+    // Total is based on the unrounded EMI; the last installment absorbs the
+    // truncation remainder so the schedule sums exactly to total_amount. In
+    // borrower-friendly mode the total follows the rounded installment instead.
+    let total_amount = if borrower_friendly {
+        monthly_installment
+            .checked_mul(tenure_months as u64)
+            .ok_or(LoanError::MathOverflow)?
+    } else {
+        (exact_installment * n).round() as u64
+    };
+    let regular_total = monthly_installment
+        .checked_mul((tenure_months - 1) as u64)
+        .ok_or(LoanError::MathOverflow)?;
+    let final_installment = total_amount
+        .checked_sub(regular_total)
+        .ok_or(LoanError::AmortizationInconsistent)?;
+
+    // Catch rounding bugs here rather than at the final payment: interest is never
+    // negative, and the regular installments plus the remainder sum exactly to the total
+    require!(total_amount >= principal_amount, LoanError::AmortizationInconsistent);
+    require!(final_installment >= monthly_installment, LoanError::AmortizationInconsistent);
+    require!(
+        regular_total.checked_add(final_installment) == Some(total_amount),
+        LoanError::AmortizationInconsistent
+    );
+
+    Ok(Schedule { monthly_installment, final_installment, total_amount })
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, StepScheduleCreated};
+
+#[derive(Accounts)]
+pub struct CreateStepSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = StepSchedule::LEN,
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump
+    )]
+    pub step_schedule: Account<'info, StepSchedule>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Each multiplier weights one installment; the loan's total_amount is shared
+/// out in proportion, with the final installment absorbing the rounding, so
+/// the repayment total is the same as on the level schedule it replaces
+pub fn handler(ctx: Context<CreateStepSchedule>, multipliers_bps: Vec<u16>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let step_schedule = &mut ctx.accounts.step_schedule;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(
+        loan.installments_paid == 0 && loan.total_repaid == 0 && loan.schedule_offset == 0,
+        LoanError::StepScheduleNotAllowed
+    );
+    require!(
+        multipliers_bps.len() == loan.tenure_months as usize && multipliers_bps.iter().all(|&m| m > 0),
+        LoanError::InvalidStepSchedule
+    );
+
+    let weight_total: u128 = multipliers_bps.iter().map(|&m| m as u128).sum();
+    let mut amounts = Vec::with_capacity(multipliers_bps.len());
+    let mut allocated = 0u64;
+    for &multiplier in &multipliers_bps[..multipliers_bps.len() - 1] {
+        let amount = ((loan.total_amount as u128) * (multiplier as u128) / weight_total) as u64;
+        allocated += amount;
+        amounts.push(amount);
+    }
+    amounts.push(loan.total_amount - allocated);
+
+    require!(
+        amounts.iter().all(|&amount| amount >= program_state.min_installment_amount),
+        LoanError::InstallmentTooSmall
+    );
+    // The first step must at least cover its interest, as on a level schedule
+    require!(
+        program_state.allow_neg_am || amounts[0] > loan.period_interest(loan.principal_amount),
+        LoanError::NegativeAmortization
+    );
+
+    step_schedule.loan = loan.key();
+    step_schedule.amounts = amounts;
+    step_schedule.bump = ctx.bumps.step_schedule;
+
+    // Low early steps can defer principal, but the final one has to be able to retire it
+    let (final_principal, final_interest) = step_schedule.split(loan, loan.tenure_months);
+    require!(
+        final_principal + final_interest == step_schedule.amount(loan.tenure_months),
+        LoanError::AmortizationInconsistent
+    );
+
+    // Affordability is judged on the largest step, not the level EMI it replaces
+    let peak_amount = step_schedule.peak_amount();
+    let added_obligations = peak_amount
+        .saturating_sub(loan.monthly_obligation)
+        .saturating_add(user_profile.counted_guarantees(program_state));
+    let dti_bps = user_profile.debt_to_income_bps_with(added_obligations)
+        .ok_or(LoanError::IncomeTooLow)?;
+    require!(dti_bps <= program_state.max_dti_bps as u64, LoanError::DtiTooHigh);
+
+    user_profile.monthly_obligations = user_profile.monthly_obligations
+        .saturating_sub(loan.monthly_obligation)
+        .saturating_add(peak_amount);
+    user_profile.last_updated = clock.unix_timestamp;
+    loan.monthly_obligation = peak_amount;
+    loan.is_stepped = true;
+
+    emit!(StepScheduleCreated {
+        loan: loan.key(),
+        step_schedule: step_schedule.key(),
+        first_installment: step_schedule.amount(1),
+        final_installment: step_schedule.amount(loan.tenure_months),
+        total_amount: loan.total_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::CreateStepSchedule, loan.key(), clock.unix_timestamp);
+
+    msg!("Step schedule created for loan {}: {} -> {}",
+        loan.loan_id, step_schedule.amount(1), step_schedule.amount(loan.tenure_months));
+
+    Ok(())
+}
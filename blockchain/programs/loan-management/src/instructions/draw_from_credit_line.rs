@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::CreditLineDrawn;
+use crate::instructions::create_loan::{amortize, check_risk_profile, originate, Origination};
+
+#[derive(Accounts)]
+pub struct DrawFromCreditLine<'info> {
+    #[account(
+        mut,
+        seeds = [b"credit-line", user.key().as_ref()],
+        bump = credit_line.bump,
+        has_one = user
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Loan::LEN,
+        seeds = [
+            b"loan",
+            user.key().as_ref(),
+            &program_state.total_loans.to_le_bytes()
+        ],
+        bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: Borrower's risk-profile PDA, always passed so the default-probability limit
+    /// can't be dodged by leaving it out; empty while the borrower has no profile
+    #[account(
+        seeds = [b"risk-profile", user.key().as_ref()],
+        bump
+    )]
+    pub risk_profile: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The borrower draws directly. Income freshness, account age and rate pricing
+/// were settled when the line was opened; a draw re-checks standing, the risk
+/// profile, the principal floor and affordability before booking the loan.
+pub fn handler(ctx: Context<DrawFromCreditLine>, amount: u64, tenure_months: u8) -> Result<()> {
+    let credit_line = &mut ctx.accounts.credit_line;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let loan = &mut ctx.accounts.loan;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(!program_state.paused, LoanError::ProgramPaused);
+    require!(
+        clock.unix_timestamp >= program_state.post_unpause_cooldown_ends_at(),
+        LoanError::PostUnpauseCooldown
+    );
+    require!(user_profile.in_good_standing(), LoanError::CreditLineNotEligible);
+    // Same 5k PKR floor as direct origination; the line limit is the ceiling
+    require!(amount >= 5_000_000_000, LoanError::InvalidLoanAmount);
+    require!(amount <= credit_line.available, LoanError::CreditLimitExceeded);
+    require!((3..=60).contains(&tenure_months), LoanError::InvalidTenure);
+    require!(
+        user_profile.active_loans < program_state.max_active_loans,
+        LoanError::ActiveLoanExists
+    );
+    require!(
+        !program_state.is_employment_blocked(&user_profile.employment_type),
+        LoanError::EmploymentTypeNotEligible
+    );
+    // The risk profile can change after the line is opened, so it is checked on every draw
+    check_risk_profile(&ctx.accounts.risk_profile, program_state)?;
+
+    let schedule = amortize(amount, credit_line.interest_rate, tenure_months, program_state)?;
+
+    let added_obligations = schedule.monthly_installment.saturating_add(user_profile.counted_guarantees(program_state));
+    let dti_bps = user_profile.debt_to_income_bps_with(added_obligations)
+        .ok_or(LoanError::IncomeTooLow)?;
+    require!(dti_bps <= program_state.max_dti_bps as u64, LoanError::DtiTooHigh);
+
+    originate(
+        loan,
+        user_profile,
+        program_state,
+        ctx.accounts.user.key(),
+        ctx.accounts.user.key(),
+        ctx.bumps.loan,
+        Origination {
+            principal_amount: amount,
+            interest_rate: credit_line.interest_rate,
+            tenure_months,
+            start_timestamp: clock.unix_timestamp,
+            schedule,
+            insurance_premium: 0,
+            rate_discount_bps: 0,
+        },
+        &clock,
+    )?;
+
+    credit_line.available -= amount;
+    credit_line.total_drawn = credit_line.total_drawn.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+    credit_line.draw_count = credit_line.draw_count.checked_add(1)
+        .ok_or(LoanError::MathOverflow)?;
+
+    emit!(CreditLineDrawn {
+        user: credit_line.user,
+        credit_line: credit_line.key(),
+        loan: loan.key(),
+        loan_id: loan.loan_id,
+        amount,
+        available: credit_line.available,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Credit line draw: Loan={}, Amount={}, Available={}", loan.loan_id, amount, credit_line.available);
+
+    Ok(())
+}
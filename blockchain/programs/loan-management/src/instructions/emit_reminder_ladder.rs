@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::PaymentReminder;
+
+#[derive(Accounts)]
+pub struct EmitReminderLadder<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<EmitReminderLadder>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let offsets = ctx.accounts.program_state.reminder_ladder_offsets;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(loan.installments_paid < loan.tenure_months, LoanError::LoanAlreadyCompleted);
+
+    let installment_number = loan.installments_paid + 1;
+    let due_timestamp = loan.due_timestamp(installment_number);
+    let elapsed = clock.unix_timestamp - due_timestamp;
+
+    // Highest stage reached; a late crank skips straight to it rather than replaying earlier ones
+    let reached = offsets.iter().filter(|offset| elapsed >= **offset).count() as u8;
+    let already_sent = if loan.last_reminder_installment == installment_number {
+        loan.last_reminder_stage
+    } else {
+        0
+    };
+
+    if reached == 0 || reached <= already_sent {
+        msg!("No new reminder stage for loan {}", loan.loan_id);
+        return Ok(());
+    }
+
+    loan.last_reminder_installment = installment_number;
+    loan.last_reminder_stage = reached;
+
+    emit!(PaymentReminder {
+        loan: loan.key(),
+        user: loan.user,
+        installment_number,
+        stage: reached,
+        due_timestamp,
+        amount_due: loan.installment_due(installment_number),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Reminder stage {} for installment {} of loan {}", reached, installment_number, loan.loan_id);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetAmortizationSchedule<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    /// Required when the loan is on a stepped plan
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+}
+
+pub fn handler(
+    ctx: Context<GetAmortizationSchedule>,
+    start_installment: u8,
+    count: u8,
+) -> Result<Vec<InstallmentDue>> {
+    let schedule = ctx.accounts.loan
+        .amortization_schedule(start_installment, count, ctx.accounts.step_schedule.as_deref())?;
+
+    msg!("Schedule for loan {}: installments {}..{}",
+        ctx.accounts.loan.loan_id, start_installment, start_installment as usize + schedule.len() - 1);
+
+    Ok(schedule)
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetAmortizationSplit<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    /// Required when the loan is on a stepped plan
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+}
+
+pub fn handler(ctx: Context<GetAmortizationSplit>, installment_number: u8) -> Result<AmortizationSplit> {
+    let split = ctx.accounts.loan
+        .amortization_split(installment_number, ctx.accounts.step_schedule.as_deref())?;
+
+    msg!("Installment {}: principal={}, interest={}, balance_after={}",
+        installment_number, split.principal_component, split.interest_component, split.balance_after);
+
+    Ok(split)
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+
+#[derive(Accounts)]
+pub struct GetCurrentFine<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    /// Required exactly when the loan is stepped
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<GetCurrentFine>, installment_number: u8) -> Result<CurrentFineView> {
+    let loan = &ctx.accounts.loan;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(
+        installment_number > 0 && installment_number <= loan.tenure_months,
+        LoanError::InvalidInstallmentNumber
+    );
+
+    // Same assessment record_payment runs, so the preview can't drift from the charge
+    let LateAssessment { days_late, fine_amount, .. } =
+        loan.assess_lateness(installment_number, clock.unix_timestamp, &ctx.accounts.program_state)?;
+
+    let base_due = loan.scheduled_due(installment_number, ctx.accounts.step_schedule.as_deref())?;
+    let total_due = base_due.checked_add(fine_amount)
+        .and_then(|due| due.checked_add(loan.servicing_fee_per_installment))
+        .ok_or(LoanError::MathOverflow)?;
+
+    Ok(CurrentFineView {
+        installment_number,
+        base_due,
+        fine: fine_amount,
+        servicing_fee: loan.servicing_fee_per_installment,
+        total_due,
+        days_late,
+    })
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetDti<'info> {
+    #[account(
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<GetDti>) -> Result<DtiView> {
+    let user_profile = &ctx.accounts.user_profile;
+    let max_dti_bps = ctx.accounts.program_state.max_dti_bps;
+
+    // monthly_obligations is maintained across every active loan at origination and close
+    let dti_bps = user_profile.debt_to_income_bps();
+    let within_limit = match dti_bps {
+        Some(dti) => dti <= max_dti_bps as u64,
+        None => user_profile.monthly_obligations == 0,
+    };
+
+    Ok(DtiView {
+        monthly_income: user_profile.monthly_income,
+        monthly_obligations: user_profile.monthly_obligations,
+        dti_bps,
+        max_dti_bps,
+        within_limit,
+    })
+}
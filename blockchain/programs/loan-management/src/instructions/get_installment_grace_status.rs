@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+
+#[derive(Accounts)]
+pub struct GetInstallmentGraceStatus<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(
+    ctx: Context<GetInstallmentGraceStatus>,
+    installment_number: u8,
+) -> Result<InstallmentGraceStatus> {
+    let loan = &ctx.accounts.loan;
+    let clock = Clock::get()?;
+
+    require!(
+        installment_number > 0 && installment_number <= loan.tenure_months,
+        LoanError::InvalidInstallmentNumber
+    );
+
+    let due_timestamp = loan.due_timestamp(installment_number);
+    let grace_ends_at = loan.grace_ends_at(installment_number, &ctx.accounts.program_state);
+    let now = clock.unix_timestamp;
+
+    // Same boundaries as assess_lateness: paying at grace_ends_at is still on time
+    let state = if now <= due_timestamp {
+        GraceState::BeforeDue
+    } else if now <= grace_ends_at {
+        GraceState::InGrace
+    } else {
+        GraceState::Overdue
+    };
+
+    Ok(InstallmentGraceStatus {
+        installment_number,
+        due_timestamp,
+        grace_ends_at,
+        state,
+        seconds_until_grace_end: (grace_ends_at - now).max(0),
+    })
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetLoanFull<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<GetLoanFull>) -> Result<LoanFullView> {
+    let loan = &ctx.accounts.loan;
+    let user_profile = &ctx.accounts.user_profile;
+    let clock = Clock::get()?;
+
+    let next_installment = loan.installments_paid.saturating_add(1);
+    let has_next = loan.status == LoanStatus::Active && next_installment <= loan.tenure_months;
+
+    let (next_due_timestamp, overdue, days_past_due, accrued_fine) = if has_next {
+        let due = loan.due_timestamp(next_installment);
+        let assessment = loan.assess_lateness(next_installment, clock.unix_timestamp, &ctx.accounts.program_state)?;
+        let days_past_due = if clock.unix_timestamp > due {
+            ((clock.unix_timestamp - due) / SECONDS_PER_DAY) as u16
+        } else {
+            0
+        };
+        (due, !assessment.on_time, days_past_due, assessment.fine_amount)
+    } else {
+        (0, false, 0, 0)
+    };
+
+    let repayment_progress_bps = if loan.total_amount > 0 {
+        ((loan.total_repaid as u128) * 10000 / (loan.total_amount as u128)).min(10000) as u16
+    } else {
+        0
+    };
+
+    Ok(LoanFullView {
+        loan_id: loan.loan_id,
+        user: loan.user,
+        status: loan.status.clone(),
+        principal_amount: loan.principal_amount,
+        interest_rate: loan.interest_rate,
+        tenure_months: loan.tenure_months,
+        monthly_installment: loan.monthly_installment,
+        final_installment: loan.final_installment,
+        total_amount: loan.total_amount,
+        outstanding_balance: loan.outstanding_balance,
+        total_repaid: loan.total_repaid,
+        total_fines: loan.total_fines,
+        start_timestamp: loan.start_timestamp,
+        end_timestamp: loan.end_timestamp,
+        installments_paid: loan.installments_paid,
+        next_due_timestamp,
+        overdue,
+        days_past_due,
+        accrued_fine,
+        repayment_progress_bps,
+        principal_repaid_to_date: loan.principal_repaid,
+        interest_repaid_to_date: loan.interest_repaid,
+        credit_score: user_profile.credit_score,
+        risk_level: user_profile.risk_level.clone(),
+    })
+}
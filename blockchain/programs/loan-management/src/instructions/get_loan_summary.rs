@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetLoanSummary<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+}
+
+pub fn handler(ctx: Context<GetLoanSummary>) -> Result<LoanSummary> {
+    let loan = &ctx.accounts.loan;
+
+    Ok(LoanSummary {
+        outstanding_balance: loan.outstanding_balance,
+        total_repaid: loan.total_repaid,
+        total_fines: loan.total_fines,
+        installments_paid: loan.installments_paid,
+        next_due_timestamp: loan.next_due_timestamp(),
+        status: loan.status.clone(),
+    })
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetPortfolioExposure<'info> {
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<GetPortfolioExposure>) -> Result<PortfolioExposure> {
+    let program_state = &ctx.accounts.program_state;
+
+    Ok(PortfolioExposure {
+        total_outstanding_principal: program_state.outstanding_principal,
+        total_outstanding_fines: program_state.outstanding_fines,
+        active_loan_count: program_state.active_loans_by_level.iter().sum(),
+        defaulted_outstanding: program_state.defaulted_outstanding,
+        at_risk_outstanding: program_state.at_risk_outstanding,
+    })
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetProgramConfig<'info> {
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<GetProgramConfig>) -> Result<ProgramConfigView> {
+    let program_state = &ctx.accounts.program_state;
+
+    Ok(ProgramConfigView {
+        authority: program_state.authority,
+        pending_authority: program_state.pending_authority,
+        fee_percentage: program_state.fee_percentage,
+        paused: program_state.paused,
+        max_dti_bps: program_state.max_dti_bps,
+        escalate_risk_on_dti_breach: program_state.escalate_risk_on_dti_breach,
+        allow_neg_am: program_state.allow_neg_am,
+        anomaly_detection_enabled: program_state.anomaly_detection_enabled,
+        anomaly_window_seconds: program_state.anomaly_window_seconds,
+        anomaly_max_loans: program_state.anomaly_max_loans,
+        anomaly_max_volume: program_state.anomaly_max_volume,
+        first_loan_fine_discount_bps: program_state.first_loan_fine_discount_bps,
+        min_payment_gap_seconds: program_state.min_payment_gap_seconds,
+        rate_bands: program_state.rate_bands,
+        max_profile_age_for_loan: program_state.max_profile_age_for_loan,
+        completion_bonus: program_state.completion_bonus,
+        default_penalty: program_state.default_penalty,
+        borrower_wallet_program: program_state.borrower_wallet_program,
+        insurance_premium_bps: program_state.insurance_premium_bps,
+        discount_score_threshold: program_state.discount_score_threshold,
+        score_discount_bps: program_state.score_discount_bps,
+        discount_rate_floor: program_state.discount_rate_floor,
+        reminder_ladder_offsets: program_state.reminder_ladder_offsets,
+        rate_oracle: program_state.rate_oracle,
+        oracle_spread_bps: program_state.oracle_spread_bps,
+        education_score_boost: program_state.education_score_boost,
+        grace_after_maturity_days: program_state.grace_after_maturity_days,
+        max_total_repayment_multiplier_bps: program_state.max_total_repayment_multiplier_bps,
+        min_income_for_recommendation: program_state.min_income_for_recommendation,
+        min_spread_bps: program_state.min_spread_bps,
+        max_spread_bps: program_state.max_spread_bps,
+        risk_level_cutoffs: program_state.risk_level_cutoffs,
+        min_installment_amount: program_state.min_installment_amount,
+        risk_multipliers: program_state.risk_multipliers,
+        npl_days: program_state.npl_days,
+        npl_penalty: program_state.npl_penalty,
+        completion_dust_tolerance: program_state.completion_dust_tolerance,
+        max_income_changes_per_window: program_state.max_income_changes_per_window,
+        income_change_window_seconds: program_state.income_change_window_seconds,
+        reject_excess_income_changes: program_state.reject_excess_income_changes,
+        min_account_age_for_loan: program_state.min_account_age_for_loan,
+        missed_payment_penalty: program_state.missed_payment_penalty,
+        retention_period_seconds: program_state.retention_period_seconds,
+        prepayment_rebate_bps: program_state.prepayment_rebate_bps,
+        max_users: program_state.max_users,
+        final_installment_grace_days: program_state.final_installment_grace_days,
+        blocked_employment_types: program_state.blocked_employment_types,
+        borrower_friendly_rounding: program_state.borrower_friendly_rounding,
+        max_loans_per_slot: program_state.max_loans_per_slot,
+        guarantor_default_penalty: program_state.guarantor_default_penalty,
+        allow_fine_only_payments: program_state.allow_fine_only_payments,
+        max_score_change_per_window: program_state.max_score_change_per_window,
+        score_change_window_seconds: program_state.score_change_window_seconds,
+        require_risk_profile: program_state.require_risk_profile,
+        max_restructures_per_loan: program_state.max_restructures_per_loan,
+        fine_cap_bps: program_state.fine_cap_bps,
+        min_fine_cap: program_state.min_fine_cap,
+        early_payment_bonus: program_state.early_payment_bonus,
+        max_active_loans: program_state.max_active_loans,
+        max_default_probability_for_loan: program_state.max_default_probability_for_loan,
+        settle_fines_on_chain: program_state.settle_fines_on_chain,
+        servicing_fee_per_installment: program_state.servicing_fee_per_installment,
+        credit_line_min_completed_loans: program_state.credit_line_min_completed_loans,
+        bonus_clawback_window_seconds: program_state.bonus_clawback_window_seconds,
+        max_start_backdate_seconds: program_state.max_start_backdate_seconds,
+        max_start_lead_seconds: program_state.max_start_lead_seconds,
+        guarantee_dti_weight_bps: program_state.guarantee_dti_weight_bps,
+        max_guarantees: program_state.max_guarantees,
+        fee_refund_window_days: program_state.fee_refund_window_days,
+        post_unpause_cooldown_seconds: program_state.post_unpause_cooldown_seconds,
+        payment_waterfall: program_state.payment_waterfall,
+        missed_installments_to_default: program_state.missed_installments_to_default,
+        max_interest_rate: LoanProgramState::MAX_INTEREST_RATE,
+        grace_period_seconds: program_state.grace_period_seconds(),
+        daily_fine_bps: program_state.daily_fine_bps as u64,
+    })
+}
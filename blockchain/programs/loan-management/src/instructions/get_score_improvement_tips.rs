@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetScoreImprovementTips<'info> {
+    #[account(
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<GetScoreImprovementTips>) -> Result<u8> {
+    let tips = ctx.accounts.user_profile.score_improvement_tips(&ctx.accounts.program_state);
+
+    msg!("Score improvement tips for {}: {:#08b}", ctx.accounts.user_profile.full_name, tips);
+
+    Ok(tips)
+}
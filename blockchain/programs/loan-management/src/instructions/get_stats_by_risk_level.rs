@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetStatsByRiskLevel<'info> {
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<GetStatsByRiskLevel>) -> Result<RiskLevelStats> {
+    let program_state = &ctx.accounts.program_state;
+
+    Ok(RiskLevelStats {
+        active_loans: program_state.active_loans_by_level,
+        outstanding: program_state.outstanding_by_level,
+        defaults: program_state.defaults_by_level,
+    })
+}
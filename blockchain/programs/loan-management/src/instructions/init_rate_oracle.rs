@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::emit_admin_action;
+
+#[derive(Accounts)]
+pub struct InitRateOracle<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = RateOracle::LEN,
+        seeds = [b"rate-oracle"],
+        bump
+    )]
+    pub rate_oracle: Account<'info, RateOracle>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitRateOracle>, feeder: Pubkey, initial_rate_bps: u16) -> Result<()> {
+    require!(
+        initial_rate_bps <= LoanProgramState::MAX_INTEREST_RATE,
+        LoanError::InvalidInterestRate
+    );
+
+    let rate_oracle = &mut ctx.accounts.rate_oracle;
+    let clock = Clock::get()?;
+
+    rate_oracle.feeder = feeder;
+    rate_oracle.current_rate_bps = initial_rate_bps;
+    rate_oracle.updated_at = clock.unix_timestamp;
+    rate_oracle.bump = ctx.bumps.rate_oracle;
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::InitRateOracle, rate_oracle.key(), clock.unix_timestamp);
+
+    msg!("Rate oracle initialized at {} bps, feeder {}", initial_rate_bps, feeder);
+
+    Ok(())
+}
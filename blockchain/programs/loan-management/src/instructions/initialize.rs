@@ -28,6 +28,93 @@ pub fn handler(ctx: Context<Initialize>, fee_percentage: u16) -> Result<()> {
     program_state.total_volume = 0;
     program_state.fee_percentage = fee_percentage;
     program_state.paused = false;
+    program_state.max_dti_bps = LoanProgramState::DEFAULT_MAX_DTI_BPS;
+    program_state.escalate_risk_on_dti_breach = false;
+    program_state.allow_neg_am = false;
+    program_state.anomaly_detection_enabled = false;
+    program_state.anomaly_window_seconds = 0;
+    program_state.anomaly_max_loans = 0;
+    program_state.anomaly_max_volume = 0;
+    program_state.anomaly_window_start = 0;
+    program_state.anomaly_window_loans = 0;
+    program_state.anomaly_window_volume = 0;
+    program_state.first_loan_fine_discount_bps = 0;
+    program_state.min_payment_gap_seconds = 0;
+    program_state.rate_bands = LoanProgramState::DEFAULT_RATE_BANDS;
+    program_state.total_recovered = 0;
+    program_state.max_profile_age_for_loan = LoanProgramState::DEFAULT_MAX_PROFILE_AGE_FOR_LOAN;
+    program_state.completion_bonus = LoanProgramState::DEFAULT_COMPLETION_BONUS;
+    program_state.default_penalty = LoanProgramState::DEFAULT_DEFAULT_PENALTY;
+    program_state.borrower_wallet_program = Pubkey::default();
+    program_state.insurance_premium_bps = 0;
+    program_state.insurance_pool = 0;
+    program_state.discount_score_threshold = LoanProgramState::MAX_CREDIT_SCORE;
+    program_state.score_discount_bps = 0;
+    program_state.discount_rate_floor = 1;
+    program_state.reminder_ladder_offsets = LoanProgramState::DEFAULT_REMINDER_LADDER_OFFSETS;
+    program_state.rate_oracle = Pubkey::default();
+    program_state.oracle_spread_bps = 0;
+    program_state.education_score_boost = LoanProgramState::DEFAULT_EDUCATION_SCORE_BOOST;
+    program_state.grace_after_maturity_days = LoanProgramState::DEFAULT_GRACE_AFTER_MATURITY_DAYS;
+    program_state.max_total_repayment_multiplier_bps = 0;
+    program_state.min_income_for_recommendation = LoanProgramState::DEFAULT_MIN_INCOME_FOR_RECOMMENDATION;
+    program_state.min_spread_bps = 0;
+    program_state.max_spread_bps = LoanProgramState::MAX_INTEREST_RATE;
+    program_state.risk_level_cutoffs = LoanProgramState::DEFAULT_RISK_LEVEL_CUTOFFS;
+    program_state.min_installment_amount = 0;
+    program_state.risk_multipliers = LoanProgramState::DEFAULT_RISK_MULTIPLIERS;
+    program_state.npl_days = LoanProgramState::DEFAULT_NPL_DAYS;
+    program_state.npl_penalty = LoanProgramState::DEFAULT_NPL_PENALTY;
+    program_state.completion_dust_tolerance = 0;
+    program_state.max_income_changes_per_window = LoanProgramState::DEFAULT_MAX_INCOME_CHANGES_PER_WINDOW;
+    program_state.income_change_window_seconds = LoanProgramState::DEFAULT_INCOME_CHANGE_WINDOW_SECONDS;
+    program_state.reject_excess_income_changes = true;
+    program_state.pending_authority = Pubkey::default();
+    program_state.min_account_age_for_loan = 0;
+    program_state.missed_payment_penalty = LoanProgramState::DEFAULT_MISSED_PAYMENT_PENALTY;
+    program_state.retention_period_seconds = LoanProgramState::DEFAULT_RETENTION_PERIOD_SECONDS;
+    program_state.prepayment_rebate_bps = 0;
+    program_state.max_users = 0;
+    program_state.grace_period_days = LoanProgramState::DEFAULT_GRACE_PERIOD_DAYS;
+    program_state.daily_fine_bps = LoanProgramState::DEFAULT_DAILY_FINE_BPS;
+    program_state.final_installment_grace_days = 0;
+    program_state.blocked_employment_types = 0;
+    program_state.borrower_friendly_rounding = false;
+    program_state.max_loans_per_slot = 0;
+    program_state.origination_slot = 0;
+    program_state.loans_in_origination_slot = 0;
+    program_state.guarantor_default_penalty = LoanProgramState::DEFAULT_GUARANTOR_DEFAULT_PENALTY;
+    program_state.allow_fine_only_payments = false;
+    program_state.max_score_change_per_window = 0;
+    program_state.score_change_window_seconds = LoanProgramState::DEFAULT_SCORE_CHANGE_WINDOW_SECONDS;
+    program_state.require_risk_profile = false;
+    program_state.max_restructures_per_loan = LoanProgramState::DEFAULT_MAX_RESTRUCTURES_PER_LOAN;
+    program_state.fine_cap_bps = LoanProgramState::DEFAULT_FINE_CAP_BPS;
+    program_state.min_fine_cap = 0;
+    program_state.early_payment_bonus = 0;
+    program_state.max_active_loans = 1;
+    program_state.total_fees_collected = 0;
+    program_state.max_default_probability_for_loan = 0;
+    program_state.settle_fines_on_chain = false;
+    program_state.servicing_fee_per_installment = 0;
+    program_state.active_loans_by_level = [0; 4];
+    program_state.outstanding_by_level = [0; 4];
+    program_state.defaults_by_level = [0; 4];
+    program_state.credit_line_min_completed_loans = LoanProgramState::DEFAULT_CREDIT_LINE_MIN_COMPLETED_LOANS;
+    program_state.bonus_clawback_window_seconds = LoanProgramState::DEFAULT_BONUS_CLAWBACK_WINDOW_SECONDS;
+    program_state.max_start_backdate_seconds = LoanProgramState::DEFAULT_MAX_START_BACKDATE_SECONDS;
+    program_state.max_start_lead_seconds = LoanProgramState::DEFAULT_MAX_START_LEAD_SECONDS;
+    program_state.guarantee_dti_weight_bps = LoanProgramState::DEFAULT_GUARANTEE_DTI_WEIGHT_BPS;
+    program_state.max_guarantees = LoanProgramState::DEFAULT_MAX_GUARANTEES;
+    program_state.fee_refund_window_days = LoanProgramState::DEFAULT_FEE_REFUND_WINDOW_DAYS;
+    program_state.post_unpause_cooldown_seconds = 0;
+    program_state.unpaused_at = 0;
+    program_state.outstanding_principal = 0;
+    program_state.outstanding_fines = 0;
+    program_state.at_risk_outstanding = 0;
+    program_state.defaulted_outstanding = 0;
+    program_state.payment_waterfall = LoanProgramState::DEFAULT_PAYMENT_WATERFALL;
+    program_state.missed_installments_to_default = 0;
     program_state.bump = ctx.bumps.program_state;
 
     msg!("Loan management program initialized with fee: {}%", fee_percentage as f64 / 100.0);
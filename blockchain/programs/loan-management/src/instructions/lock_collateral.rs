@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, CollateralLocked};
+
+#[derive(Accounts)]
+pub struct LockCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Collateral::LEN,
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump
+    )]
+    pub collateral: Account<'info, Collateral>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<LockCollateral>,
+    collateral_type: CollateralType,
+    declared_value: u64,
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let collateral = &mut ctx.accounts.collateral;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(declared_value > 0, LoanError::InvalidCollateralValue);
+
+    collateral.loan = loan.key();
+    collateral.collateral_type = collateral_type.clone();
+    collateral.declared_value = declared_value;
+    collateral.locked = true;
+    collateral.seized = false;
+    collateral.locked_at = clock.unix_timestamp;
+    collateral.bump = ctx.bumps.collateral;
+
+    loan.is_secured = true;
+
+    emit!(CollateralLocked {
+        loan: loan.key(),
+        user: loan.user,
+        collateral_type,
+        declared_value,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::LockCollateral, loan.key(), clock.unix_timestamp);
+
+    msg!("Collateral worth {} locked against loan {}", declared_value, loan.loan_id);
+
+    Ok(())
+}
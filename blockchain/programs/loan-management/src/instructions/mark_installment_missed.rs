@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, InstallmentMissed};
+
+#[derive(Accounts)]
+#[instruction(installment_number: u8)]
+pub struct MarkInstallmentMissed<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", user_profile.authority.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Payment record PDA for the installment; must not exist yet
+    #[account(
+        seeds = [
+            b"payment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub payment_record: AccountInfo<'info>,
+
+    /// CHECK: Part-payment tracker PDA; a completed tracker counts as paid
+    #[account(
+        seeds = [
+            b"installment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub installment_state: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<MarkInstallmentMissed>, installment_number: u8) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(
+        installment_number > 0 && installment_number <= loan.tenure_months,
+        LoanError::InvalidInstallmentNumber
+    );
+
+    let settled_in_parts = {
+        let info = &ctx.accounts.installment_state;
+        !info.data_is_empty()
+            && InstallmentState::try_deserialize(&mut &info.try_borrow_data()?[..])?.completed
+    };
+
+    // Missed means past grace with no payment on record
+    require!(
+        clock.unix_timestamp > loan.grace_ends_at(installment_number, program_state)
+            && ctx.accounts.payment_record.data_is_empty()
+            && !settled_in_parts,
+        LoanError::InstallmentNotMissed
+    );
+
+    // Tenure is capped at 60, so every installment has its own bit
+    let bit = 1u64 << (installment_number - 1);
+    require!(loan.missed_installments & bit == 0, LoanError::InstallmentAlreadyMissed);
+    loan.missed_installments |= bit;
+
+    user_profile.missed_payments = user_profile.missed_payments.checked_add(1)
+        .ok_or(LoanError::MathOverflow)?;
+    user_profile.adjust_credit_score(-(program_state.missed_payment_penalty as i32), program_state, clock.unix_timestamp);
+    user_profile.sync_risk_level(program_state);
+    user_profile.last_updated = clock.unix_timestamp;
+
+    emit!(InstallmentMissed {
+        loan: loan.key(),
+        user: loan.user,
+        installment_number,
+        missed_payments: user_profile.missed_payments,
+        credit_score: user_profile.credit_score,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::MarkInstallmentMissed, loan.key(), clock.unix_timestamp);
+
+    msg!("Installment {} of loan {} marked missed", installment_number, loan.loan_id);
+
+    Ok(())
+}
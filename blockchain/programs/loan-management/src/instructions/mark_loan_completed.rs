@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::LoanCompleted;
+use crate::events::{emit_admin_action, LoanCompleted};
+use crate::instructions::add_guarantor::release_guarantor;
 
 #[derive(Accounts)]
 pub struct MarkLoanCompleted<'info> {
@@ -19,38 +20,88 @@ pub struct MarkLoanCompleted<'info> {
     )]
     pub user_profile: Account<'info, UserProfile>,
 
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == authority.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan has a guarantor, so the guarantee stops counting against them
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+
     pub authority: Signer<'info>,
 }
 
 pub fn handler(ctx: Context<MarkLoanCompleted>) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
     let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
     let clock = Clock::get()?;
 
     require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
-    require!(loan.outstanding_balance == 0, LoanError::InsufficientPayment);
+    require!(
+        loan.outstanding_balance <= program_state.completion_dust_tolerance,
+        LoanError::InsufficientPayment
+    );
 
-    // Mark as completed
-    loan.status = LoanStatus::Completed;
-    loan.completed_timestamp = Some(clock.unix_timestamp);
+    // Absorb any rounding residual so a completed loan always carries a zero balance
+    let dust_absorbed = loan.outstanding_balance;
+    loan.outstanding_balance = 0;
 
-    // Update user profile
-    user_profile.active_loans = user_profile.active_loans.saturating_sub(1);
-    user_profile.completed_loans = user_profile.completed_loans.checked_add(1)
-        .ok_or(LoanError::MathOverflow)?;
-    
-    // Improve credit score for completing loan
-    user_profile.credit_score = user_profile.credit_score.saturating_add(20).min(850);
-    user_profile.last_updated = clock.unix_timestamp;
+    complete_loan(loan, user_profile, program_state, clock.unix_timestamp)?;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+    release_guarantor(loan, ctx.accounts.guarantor.as_deref(), ctx.accounts.guarantor_profile.as_deref_mut())?;
 
     emit!(LoanCompleted {
         loan_id: loan.loan_id,
         user: loan.user,
         total_repaid: loan.total_repaid,
+        dust_absorbed,
         completed_timestamp: clock.unix_timestamp,
     });
 
+    emit_admin_action(ctx.accounts.authority.key(), AdminActionCode::MarkLoanCompleted, loan.key(), clock.unix_timestamp);
+
     msg!("Loan {} marked as completed", loan.loan_id);
 
     Ok(())
 }
+
+/// Close out a fully repaid loan and credit the borrower; shared by admin completion and prepayment
+pub(crate) fn complete_loan(
+    loan: &mut Loan,
+    user_profile: &mut UserProfile,
+    program_state: &LoanProgramState,
+    now: i64,
+) -> Result<()> {
+    loan.validate_status_timestamps()?;
+    loan.status = LoanStatus::Completed;
+    loan.completed_timestamp = Some(now);
+    loan.validate_status_timestamps()?;
+
+    // Update user profile
+    user_profile.active_loans = user_profile.active_loans.saturating_sub(1);
+    user_profile.monthly_obligations = user_profile.monthly_obligations.saturating_sub(loan.monthly_obligation);
+    user_profile.completed_loans = user_profile.completed_loans.checked_add(1)
+        .ok_or(LoanError::MathOverflow)?;
+
+    // Improve credit score for completing loan
+    let bonus = user_profile.adjust_credit_score(program_state.completion_bonus as i32, program_state, now);
+    user_profile.last_completion_at = now;
+    user_profile.last_completion_bonus = bonus.max(0) as u16;
+    user_profile.sync_risk_level(program_state);
+    user_profile.last_updated = now;
+
+    Ok(())
+}
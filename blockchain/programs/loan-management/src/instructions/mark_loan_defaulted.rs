@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::LoanDefaulted;
+use crate::events::{emit_admin_action, CollateralSeized, GuarantorLiabilityTriggered, LoanDefaulted};
+use crate::instructions::add_guarantor::guarantor_accounts;
 
 #[derive(Accounts)]
 pub struct MarkLoanDefaulted<'info> {
@@ -19,41 +20,176 @@ pub struct MarkLoanDefaulted<'info> {
     )]
     pub user_profile: Account<'info, UserProfile>,
 
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan is secured
+    #[account(
+        mut,
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump = collateral.bump
+    )]
+    pub collateral: Option<Account<'info, Collateral>>,
+
+    /// Required when the loan has a guarantor
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 }
 
 pub fn handler(ctx: Context<MarkLoanDefaulted>) -> Result<()> {
-    let loan = &mut ctx.accounts.loan;
-    let user_profile = &mut ctx.accounts.user_profile;
     let clock = Clock::get()?;
 
-    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
-    require!(loan.outstanding_balance > 0, LoanError::LoanAlreadyCompleted);
+    require!(ctx.accounts.loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(ctx.accounts.loan.outstanding_balance > 0, LoanError::LoanAlreadyCompleted);
+
+    apply_default(
+        &mut ctx.accounts.loan,
+        &mut ctx.accounts.user_profile,
+        &ctx.accounts.program_state,
+        ctx.accounts.collateral.as_deref_mut(),
+        clock.unix_timestamp,
+        DefaultReason::Manual,
+    )?;
+    ctx.accounts.program_state.sync_loan_segment(
+        &mut ctx.accounts.loan,
+        &ctx.accounts.user_profile.risk_level,
+    );
+    trigger_guarantor(
+        &ctx.accounts.loan,
+        ctx.accounts.guarantor.as_deref(),
+        ctx.accounts.guarantor_profile.as_deref_mut(),
+        &ctx.accounts.program_state,
+        clock.unix_timestamp,
+    )?;
+
+    emit_admin_action(
+        ctx.accounts.admin.key(),
+        AdminActionCode::MarkLoanDefaulted,
+        ctx.accounts.loan.key(),
+        clock.unix_timestamp,
+    );
+
+    Ok(())
+}
+
+/// Move an active loan to Defaulted and penalize the borrower; shared by the admin and maturity paths
+pub(crate) fn apply_default(
+    loan: &mut Loan,
+    user_profile: &mut UserProfile,
+    program_state: &LoanProgramState,
+    collateral: Option<&mut Collateral>,
+    now: i64,
+    reason: DefaultReason,
+) -> Result<()> {
+    require!(loan.is_secured == collateral.is_some(), LoanError::CollateralAccountMismatch);
 
     // Mark as defaulted
+    loan.validate_status_timestamps()?;
     loan.status = LoanStatus::Defaulted;
-    loan.defaulted_timestamp = Some(clock.unix_timestamp);
+    loan.defaulted_timestamp = Some(now);
+    loan.validate_status_timestamps()?;
 
     // Update user profile
     user_profile.active_loans = user_profile.active_loans.saturating_sub(1);
+    user_profile.monthly_obligations = user_profile.monthly_obligations.saturating_sub(loan.monthly_obligation);
     user_profile.defaulted_loans = user_profile.defaulted_loans.checked_add(1)
         .ok_or(LoanError::MathOverflow)?;
     
+    // A bonus earned just before defaulting wasn't a sign of reliability, so it goes too
+    let window = program_state.bonus_clawback_window_seconds;
+    let bonus_clawed_back = if window > 0 && now - user_profile.last_completion_at <= window {
+        user_profile.last_completion_bonus
+    } else {
+        0
+    };
+    user_profile.last_completion_bonus = 0;
+
     // Severely impact credit score
-    user_profile.credit_score = user_profile.credit_score.saturating_sub(100).max(300);
+    user_profile.credit_score = user_profile.credit_score
+        .saturating_sub(program_state.default_penalty)
+        .saturating_sub(bonus_clawed_back)
+        .max(LoanProgramState::MIN_CREDIT_SCORE);
+    // A default deliberately overrides the score-derived level until the next sync
     user_profile.risk_level = RiskLevel::Critical;
-    user_profile.last_updated = clock.unix_timestamp;
+    user_profile.last_updated = now;
 
     emit!(LoanDefaulted {
         loan_id: loan.loan_id,
         user: loan.user,
         outstanding_balance: loan.outstanding_balance,
         total_fines: loan.total_fines,
-        defaulted_timestamp: clock.unix_timestamp,
+        defaulted_timestamp: now,
+        reason,
+        bonus_clawed_back,
     });
 
+    if let Some(collateral) = collateral {
+        collateral.seized = true;
+
+        emit!(CollateralSeized {
+            loan: collateral.loan,
+            user: loan.user,
+            collateral_type: collateral.collateral_type.clone(),
+            declared_value: collateral.declared_value,
+            outstanding_balance: loan.outstanding_balance,
+            timestamp: now,
+        });
+    }
+
     msg!("Loan {} marked as defaulted", loan.loan_id);
 
     Ok(())
 }
+
+/// Hold a defaulted loan's guarantor liable and apply their score penalty; shared by both default paths
+pub(crate) fn trigger_guarantor(
+    loan: &Loan,
+    guarantor: Option<&Guarantor>,
+    guarantor_profile: Option<&mut UserProfile>,
+    program_state: &LoanProgramState,
+    now: i64,
+) -> Result<()> {
+    let Some((guarantor, guarantor_profile)) = guarantor_accounts(loan, guarantor, guarantor_profile)? else {
+        return Ok(());
+    };
+    guarantor_profile.release_guarantee(guarantor);
+
+    // Goes through the score window like any other penalty, so it reports what was actually applied
+    let applied = guarantor_profile.adjust_credit_score(
+        -(program_state.guarantor_default_penalty as i32),
+        program_state,
+        now,
+    );
+    let score_penalty = applied.unsigned_abs() as u16;
+    guarantor_profile.sync_risk_level(program_state);
+    guarantor_profile.last_updated = now;
+
+    emit!(GuarantorLiabilityTriggered {
+        loan: guarantor.loan,
+        borrower: loan.user,
+        guarantor: guarantor.guarantor,
+        guaranteed_amount: guarantor.guaranteed_amount,
+        liability: guarantor.guaranteed_amount.min(loan.outstanding_balance),
+        score_penalty,
+        new_credit_score: guarantor_profile.credit_score,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::LoanNonPerforming;
+
+#[derive(Accounts)]
+pub struct MarkNonPerforming<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+pub fn handler(ctx: Context<MarkNonPerforming>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(!loan.non_performing, LoanError::AlreadyNonPerforming);
+
+    // Permissionless crank: the clock alone decides, so anyone can trigger it
+    let days_past_due = loan.days_past_due(clock.unix_timestamp);
+    require!(days_past_due >= program_state.npl_days as i64, LoanError::NotNonPerforming);
+
+    loan.non_performing = true;
+
+    user_profile.credit_score = user_profile.credit_score
+        .saturating_sub(program_state.npl_penalty)
+        .max(LoanProgramState::MIN_CREDIT_SCORE);
+    user_profile.sync_risk_level(program_state);
+    user_profile.last_updated = clock.unix_timestamp;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    emit!(LoanNonPerforming {
+        loan: loan.key(),
+        user: loan.user,
+        days_past_due,
+        credit_score: user_profile.credit_score,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Loan {} marked non-performing at {} days past due", loan.loan_id, days_past_due);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::emit_admin_action;
+
+#[derive(Accounts)]
+pub struct MigrateProgramState<'info> {
+    /// CHECK: Read by hand because an unmigrated account is too short to deserialize
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump,
+        owner = crate::ID
+    )]
+    pub program_state: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a pre-fine-params program state to the current layout; see
+/// LoanProgramState::migrate_pre_fine_params for the defaults it fills in
+pub fn handler(ctx: Context<MigrateProgramState>) -> Result<()> {
+    let info = &ctx.accounts.program_state;
+    let old_len = LoanProgramState::PRE_FINE_PARAMS_LEN;
+
+    let migrated = LoanProgramState::migrate_pre_fine_params(&info.try_borrow_data()?)?;
+    require!(migrated.authority == ctx.accounts.authority.key(), LoanError::Unauthorized);
+
+    let rent_needed = Rent::get()?
+        .minimum_balance(LoanProgramState::LEN)
+        .saturating_sub(info.lamports());
+    if rent_needed > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            rent_needed,
+        )?;
+    }
+
+    info.realloc(LoanProgramState::LEN, true)?;
+    {
+        let mut data = info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        migrated.try_serialize(&mut writer)?;
+    }
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::MigrateProgramState,
+        info.key(),
+        Clock::get()?.unix_timestamp,
+    );
+
+    msg!("Program state migrated from {} to {} bytes", old_len, LoanProgramState::LEN);
+
+    Ok(())
+}
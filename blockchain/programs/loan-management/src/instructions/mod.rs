@@ -8,6 +8,61 @@ pub mod mark_loan_defaulted;
 pub mod mark_loan_completed;
 pub mod waive_fine;
 pub mod get_credit_score;
+pub mod update_program_config;
+pub mod add_loan_note;
+pub mod get_amortization_split;
+pub mod project_payoff;
+pub mod verify_loan_invariants;
+pub mod refresh_loan_recommendation;
+pub mod get_loan_full;
+pub mod record_recovery;
+pub mod get_dti;
+pub mod adjust_program_counters;
+pub mod register_wallet_user;
+pub mod get_program_config;
+pub mod claim_insurance;
+pub mod emit_reminder_ladder;
+pub mod init_rate_oracle;
+pub mod update_oracle_rate;
+pub mod record_financial_education;
+pub mod auto_default_matured;
+pub mod get_installment_grace_status;
+pub mod update_notification_preferences;
+pub mod update_risk_multipliers;
+pub mod mark_non_performing;
+pub mod get_current_fine;
+pub mod cancel_loan;
+pub mod set_pause_state;
+pub mod transfer_authority;
+pub mod accept_authority;
+pub mod mark_installment_missed;
+pub mod set_co_authority;
+pub mod record_partial_payment;
+pub mod auto_close_terminal;
+pub mod prepay_loan;
+pub mod set_max_users;
+pub mod update_fine_params;
+pub mod migrate_program_state;
+pub mod set_employment_blocklist;
+pub mod get_loan_summary;
+pub mod get_score_improvement_tips;
+pub mod lock_collateral;
+pub mod release_collateral;
+pub mod add_guarantor;
+pub mod compute_risk_score;
+pub mod restructure_loan;
+pub mod close_loan;
+pub mod set_max_active_loans;
+pub mod init_treasury;
+pub mod withdraw_treasury;
+pub mod reverse_payment;
+pub mod refresh_loan_segment;
+pub mod get_stats_by_risk_level;
+pub mod open_credit_line;
+pub mod draw_from_credit_line;
+pub mod get_amortization_schedule;
+pub mod create_step_schedule;
+pub mod get_portfolio_exposure;
 
 pub use initialize::*;
 pub use register_user::*;
@@ -19,3 +74,58 @@ pub use mark_loan_defaulted::*;
 pub use mark_loan_completed::*;
 pub use waive_fine::*;
 pub use get_credit_score::*;
+pub use update_program_config::*;
+pub use add_loan_note::*;
+pub use get_amortization_split::*;
+pub use project_payoff::*;
+pub use verify_loan_invariants::*;
+pub use refresh_loan_recommendation::*;
+pub use get_loan_full::*;
+pub use record_recovery::*;
+pub use get_dti::*;
+pub use adjust_program_counters::*;
+pub use register_wallet_user::*;
+pub use get_program_config::*;
+pub use claim_insurance::*;
+pub use emit_reminder_ladder::*;
+pub use init_rate_oracle::*;
+pub use update_oracle_rate::*;
+pub use record_financial_education::*;
+pub use auto_default_matured::*;
+pub use get_installment_grace_status::*;
+pub use update_notification_preferences::*;
+pub use update_risk_multipliers::*;
+pub use mark_non_performing::*;
+pub use get_current_fine::*;
+pub use cancel_loan::*;
+pub use set_pause_state::*;
+pub use transfer_authority::*;
+pub use accept_authority::*;
+pub use mark_installment_missed::*;
+pub use set_co_authority::*;
+pub use record_partial_payment::*;
+pub use auto_close_terminal::*;
+pub use prepay_loan::*;
+pub use set_max_users::*;
+pub use update_fine_params::*;
+pub use migrate_program_state::*;
+pub use set_employment_blocklist::*;
+pub use get_loan_summary::*;
+pub use get_score_improvement_tips::*;
+pub use lock_collateral::*;
+pub use release_collateral::*;
+pub use add_guarantor::*;
+pub use compute_risk_score::*;
+pub use restructure_loan::*;
+pub use close_loan::*;
+pub use set_max_active_loans::*;
+pub use init_treasury::*;
+pub use withdraw_treasury::*;
+pub use reverse_payment::*;
+pub use refresh_loan_segment::*;
+pub use get_stats_by_risk_level::*;
+pub use open_credit_line::*;
+pub use draw_from_credit_line::*;
+pub use get_amortization_schedule::*;
+pub use create_step_schedule::*;
+pub use get_portfolio_exposure::*;
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, CreditLineOpened};
+
+#[derive(Accounts)]
+pub struct OpenCreditLine<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = CreditLine::LEN,
+        seeds = [b"credit-line", user.key().as_ref()],
+        bump
+    )]
+    pub credit_line: Account<'info, CreditLine>,
+
+    #[account(
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The line is underwritten once here: the rate is priced against the
+/// borrower's band now, and draws later reuse it without re-pricing
+pub fn handler(ctx: Context<OpenCreditLine>, limit: u64, interest_rate: u16) -> Result<()> {
+    let credit_line = &mut ctx.accounts.credit_line;
+    let user_profile = &ctx.accounts.user_profile;
+    let program_state = &ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(!program_state.paused, LoanError::ProgramPaused);
+    require!(
+        user_profile.completed_loans >= program_state.credit_line_min_completed_loans as u16
+            && user_profile.in_good_standing(),
+        LoanError::CreditLineNotEligible
+    );
+    // Never more than a single direct loan could borrow
+    require!(limit > 0 && limit <= 500_000_000_000, LoanError::InvalidLoanAmount);
+
+    let band = program_state.rate_band_for(user_profile.credit_score);
+    require!(
+        interest_rate > 0
+            && interest_rate <= LoanProgramState::MAX_INTEREST_RATE
+            && interest_rate >= band.min_rate
+            && interest_rate <= band.max_rate,
+        LoanError::InvalidInterestRate
+    );
+
+    credit_line.user = ctx.accounts.user.key();
+    credit_line.limit = limit;
+    credit_line.available = limit;
+    credit_line.interest_rate = interest_rate;
+    credit_line.total_drawn = 0;
+    credit_line.draw_count = 0;
+    credit_line.opened_timestamp = clock.unix_timestamp;
+    credit_line.rent_payer = ctx.accounts.admin.key();
+    credit_line.bump = ctx.bumps.credit_line;
+
+    emit!(CreditLineOpened {
+        user: credit_line.user,
+        credit_line: credit_line.key(),
+        limit,
+        interest_rate,
+        completed_loans: user_profile.completed_loans,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::OpenCreditLine, credit_line.user, clock.unix_timestamp);
+
+    msg!("Credit line opened: User={}, Limit={}", credit_line.user, limit);
+
+    Ok(())
+}
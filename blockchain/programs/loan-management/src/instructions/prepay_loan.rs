@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{LoanCompleted, LoanPrepaid};
+use crate::instructions::add_guarantor::release_guarantor;
+use crate::instructions::mark_loan_completed::complete_loan;
+
+#[derive(Accounts)]
+pub struct PrepayLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", user_profile.authority.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump,
+        has_one = user
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    /// Required when the loan has a guarantor, so the guarantee stops counting against them
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+
+    pub payer: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<PrepayLoan>, amount: u64) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    // Fines on overdue installments would otherwise be skipped by settling the balance directly
+    require!(loan.days_past_due(clock.unix_timestamp) == 0, LoanError::PrepaymentWhileOverdue);
+
+    let outstanding_before = loan.outstanding_balance;
+    let rebate = loan.prepayment_rebate(program_state)?;
+    let payoff = outstanding_before.saturating_sub(rebate);
+    require!(amount >= payoff, LoanError::InsufficientPayment);
+
+    let installments_remaining = loan.tenure_months.saturating_sub(loan.installments_paid);
+
+    // The payoff retires the remaining principal; what's left of it is interest
+    let principal_paid = loan.remaining_principal()?.min(payoff);
+    loan.principal_repaid = loan.principal_repaid.checked_add(principal_paid)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.interest_repaid = loan.interest_repaid.checked_add(payoff - principal_paid)
+        .ok_or(LoanError::MathOverflow)?;
+
+    loan.total_repaid = loan.total_repaid.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.outstanding_balance = 0;
+    loan.last_payment_at = clock.unix_timestamp;
+
+    user_profile.total_repaid = user_profile.total_repaid.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+
+    complete_loan(loan, user_profile, program_state, clock.unix_timestamp)?;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+    release_guarantor(loan, ctx.accounts.guarantor.as_deref(), ctx.accounts.guarantor_profile.as_deref_mut())?;
+
+    emit!(LoanPrepaid {
+        loan: loan.key(),
+        user: loan.user,
+        amount,
+        outstanding_before,
+        rebate,
+        installments_remaining,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit!(LoanCompleted {
+        loan_id: loan.loan_id,
+        user: loan.user,
+        total_repaid: loan.total_repaid,
+        dust_absorbed: 0,
+        completed_timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Loan {} prepaid: Amount={}, Rebate={}", loan.loan_id, amount, rebate);
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+
+#[derive(Accounts)]
+pub struct ProjectPayoff<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+}
+
+pub fn handler(ctx: Context<ProjectPayoff>, monthly_payment: u64) -> Result<PayoffProjection> {
+    let loan = &ctx.accounts.loan;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(monthly_payment > 0, LoanError::InvalidPaymentAmount);
+
+    let projection = loan.project_payoff(monthly_payment)?;
+
+    msg!("Payoff projection for loan {}: pays_off={}, installments={}",
+        loan.loan_id, projection.pays_off, projection.estimated_installments_remaining);
+
+    Ok(projection)
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, EducationCompleted};
+
+#[derive(Accounts)]
+pub struct RecordFinancialEducation<'info> {
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RecordFinancialEducation>) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    let score_boost = ctx.accounts.program_state.education_score_boost;
+    let clock = Clock::get()?;
+
+    require!(!user_profile.education_completed, LoanError::EducationAlreadyRecorded);
+
+    user_profile.education_completed = true;
+    user_profile.adjust_credit_score(score_boost as i32, &ctx.accounts.program_state, clock.unix_timestamp);
+    user_profile.last_updated = clock.unix_timestamp;
+
+    emit!(EducationCompleted {
+        user: user_profile.authority,
+        score_boost,
+        new_credit_score: user_profile.credit_score,
+        verified_by: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::RecordFinancialEducation, user_profile.authority, clock.unix_timestamp);
+
+    msg!("Financial education recorded for {}", user_profile.full_name);
+
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{FineOnlyPayment, PartialPaymentRecorded, PaymentRecorded};
+use crate::instructions::record_payment::{book_installment_split, credit_installment, cure_if_caught_up, deposit_to_treasury};
+
+#[derive(Accounts)]
+#[instruction(installment_number: u8)]
+pub struct RecordPartialPayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", user_profile.authority.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump,
+        has_one = user
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = InstallmentState::LEN,
+        seeds = [
+            b"installment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub installment_state: Account<'info, InstallmentState>,
+
+    /// CHECK: Payment record PDA; must be empty since a full payment already settled the installment
+    #[account(
+        seeds = [
+            b"payment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub payment_record: AccountInfo<'info>,
+
+    /// Required exactly when the loan is stepped
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Receives fines and the servicing fee as they settle; required only when settle_fines_on_chain is set
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RecordPartialPayment>, installment_number: u8, amount: u64) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let installment_state = &mut ctx.accounts.installment_state;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(installment_number > 0 && installment_number <= loan.tenure_months, LoanError::InvalidInstallmentNumber);
+    require!(amount > 0, LoanError::InvalidPaymentAmount);
+    require!(ctx.accounts.payment_record.data_is_empty(), LoanError::InstallmentAlreadyPaid);
+    require!(!installment_state.completed, LoanError::InstallmentAlreadyPaid);
+    loan.check_payment_gap(installment_number, program_state, clock.unix_timestamp)?;
+
+    if installment_state.loan == Pubkey::default() {
+        installment_state.loan = loan.key();
+        installment_state.installment_number = installment_number;
+        installment_state.rent_payer = ctx.accounts.payer.key();
+        installment_state.bump = ctx.bumps.installment_state;
+    }
+
+    // The fine is reassessed on every part, so paying slowly while overdue costs more
+    let LateAssessment { on_time, days_late, fine_amount } =
+        loan.assess_lateness(installment_number, clock.unix_timestamp, program_state)?;
+
+    // Paying exactly the outstanding fine clears it and leaves the installment itself due
+    let fine_outstanding = fine_amount.saturating_sub(installment_state.fine_paid);
+    let fine_only = program_state.allow_fine_only_payments
+        && fine_outstanding > 0
+        && amount == fine_outstanding;
+
+    // Earlier parts already came off the balance; add them back so the final installment isn't shrunk
+    let step_schedule = ctx.accounts.step_schedule.as_deref();
+    let scheduled_amount = loan.scheduled_amount(installment_number, step_schedule)?;
+    let applied_before = installment_state.interest_paid.saturating_add(installment_state.principal_paid);
+    let base_due = if installment_number == loan.tenure_months {
+        scheduled_amount.min(loan.outstanding_balance.saturating_add(applied_before))
+    } else {
+        scheduled_amount
+    };
+
+    // The installment's interest comes off first when a capped final installment is short
+    let (_, scheduled_interest) = loan.installment_split(installment_number, step_schedule)?;
+    let interest_due = scheduled_interest.min(base_due);
+    let principal_due = base_due - interest_due;
+    let (fine_part, interest_part, principal_part) = if fine_only {
+        (amount, 0, 0)
+    } else {
+        program_state.payment_waterfall.allocate(
+            amount,
+            fine_outstanding,
+            interest_due.saturating_sub(installment_state.interest_paid),
+            principal_due.saturating_sub(installment_state.principal_paid),
+        )
+    };
+    let applied_now = interest_part + principal_part;
+    let servicing_fee = loan.servicing_fee_per_installment;
+    let total_due = base_due.checked_add(fine_amount)
+        .and_then(|due| due.checked_add(servicing_fee))
+        .ok_or(LoanError::MathOverflow)?;
+
+    let fine_pending_before = installment_state.fine_amount.saturating_sub(installment_state.fine_paid);
+    installment_state.amount_paid = installment_state.amount_paid.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+    installment_state.fine_amount = fine_amount;
+    installment_state.on_time = on_time;
+    installment_state.days_late = days_late;
+    installment_state.last_payment_at = clock.unix_timestamp;
+
+    // Each part books the interest and principal it settled, so the split reflects the waterfall
+    installment_state.interest_paid = installment_state.interest_paid.checked_add(interest_part)
+        .ok_or(LoanError::MathOverflow)?;
+    installment_state.principal_paid = installment_state.principal_paid.checked_add(principal_part)
+        .ok_or(LoanError::MathOverflow)?;
+    if installment_number > loan.schedule_offset {
+        loan.interest_repaid = loan.interest_repaid.checked_add(interest_part)
+            .ok_or(LoanError::MathOverflow)?;
+        loan.principal_repaid = loan.principal_repaid.checked_add(principal_part)
+            .ok_or(LoanError::MathOverflow)?;
+    }
+
+    // Fine cleared ahead of the installment is settled now rather than at completion
+    if fine_part > 0 {
+        installment_state.fine_paid = installment_state.fine_paid.checked_add(fine_part)
+            .ok_or(LoanError::MathOverflow)?;
+        loan.total_fines = loan.total_fines.checked_add(fine_part)
+            .ok_or(LoanError::MathOverflow)?;
+        deposit_to_treasury(
+            program_state,
+            ctx.accounts.treasury.as_mut(),
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            loan.key(),
+            fine_part,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    if fine_only {
+        emit!(FineOnlyPayment {
+            loan: loan.key(),
+            user: ctx.accounts.user.key(),
+            installment_number,
+            fine_paid: amount,
+            installment_remaining: base_due.saturating_sub(applied_before),
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    // Parts retire balance only up to the scheduled installment; the fine and any excess don't
+    loan.total_repaid = loan.total_repaid.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.outstanding_balance = loan.outstanding_balance.saturating_sub(applied_now);
+    loan.last_payment_at = clock.unix_timestamp;
+    loan.last_payment_installment = installment_number;
+
+    user_profile.total_repaid = user_profile.total_repaid.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+    user_profile.last_updated = clock.unix_timestamp;
+
+    let completed = installment_state.amount_paid >= total_due;
+    // Until the installment completes, the reassessed fine less what's been paid toward it is still owed
+    let fine_pending_after = if completed {
+        0
+    } else {
+        installment_state.fine_amount.saturating_sub(installment_state.fine_paid)
+    };
+    loan.pending_fines = loan.pending_fines
+        .saturating_sub(fine_pending_before)
+        .saturating_add(fine_pending_after);
+    if completed {
+        installment_state.completed = true;
+
+        // Whatever earlier parts already cleared of the fine is in total_fines and the treasury
+        let fine_settled = fine_amount.saturating_sub(installment_state.fine_paid);
+        loan.total_fines = loan.total_fines.checked_add(fine_settled)
+            .ok_or(LoanError::MathOverflow)?;
+        loan.total_servicing_fees = loan.total_servicing_fees.checked_add(servicing_fee)
+            .ok_or(LoanError::MathOverflow)?;
+        let settled_on_chain = fine_settled.checked_add(servicing_fee)
+            .ok_or(LoanError::MathOverflow)?;
+        deposit_to_treasury(
+            program_state,
+            ctx.accounts.treasury.as_mut(),
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            loan.key(),
+            settled_on_chain,
+            clock.unix_timestamp,
+        )?;
+        loan.installments_paid = loan.installments_paid.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+        loan.paid_installments |= 1u64 << (installment_number - 1);
+        // Settled without a PaymentRecord, so nothing paid up to here can be reversed
+        loan.reversible_installment = 0;
+        // The carried fine was part of this installment's fine, now settled
+        if installment_number == loan.schedule_offset + 1 {
+            loan.carried_fine = 0;
+        }
+        // The parts were booked as they came in; book the installment's split as a whole instead
+        if installment_number > loan.schedule_offset {
+            loan.interest_repaid = loan.interest_repaid.saturating_sub(installment_state.interest_paid);
+            loan.principal_repaid = loan.principal_repaid.saturating_sub(installment_state.principal_paid);
+        }
+        book_installment_split(loan, installment_number, step_schedule, clock.unix_timestamp)?;
+
+        cure_if_caught_up(loan, program_state, clock.unix_timestamp);
+        let early = loan.is_early(installment_number, clock.unix_timestamp);
+        credit_installment(user_profile, on_time, early, program_state, clock.unix_timestamp)?;
+
+        emit!(PaymentRecorded {
+            loan: loan.key(),
+            user: ctx.accounts.user.key(),
+            installment_number,
+            amount: installment_state.amount_paid,
+            fine_amount,
+            servicing_fee,
+            payment_timestamp: clock.unix_timestamp,
+            on_time,
+            days_late,
+        });
+    }
+
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    emit!(PartialPaymentRecorded {
+        loan: loan.key(),
+        user: ctx.accounts.user.key(),
+        installment_number,
+        amount,
+        amount_paid: installment_state.amount_paid,
+        total_due,
+        completed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Partial payment recorded: Loan={}, Installment={}, Paid={}/{}",
+        loan.loan_id, installment_number, installment_state.amount_paid, total_due);
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::PaymentRecorded;
+use crate::events::{LoanCured, PaymentRecorded, PaymentSplit, TreasuryDeposited};
 
 #[derive(Accounts)]
 #[instruction(installment_number: u8)]
@@ -21,8 +22,9 @@ pub struct RecordPayment<'info> {
     )]
     pub user_profile: Account<'info, UserProfile>,
 
+    // init_if_needed so a repeat payment reaches the handler and fails with InstallmentAlreadyPaid
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
         space = PaymentRecord::LEN,
         seeds = [
@@ -34,6 +36,39 @@ pub struct RecordPayment<'info> {
     )]
     pub payment_record: Account<'info, PaymentRecord>,
 
+    /// CHECK: Part-payment tracker PDA; must be empty so an installment isn't settled twice
+    #[account(
+        seeds = [
+            b"installment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub installment_state: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required exactly when the loan is stepped
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+
+    /// Receives the fine and servicing fee; required only when settle_fines_on_chain is set
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
     /// CHECK: User authority
     pub user: AccountInfo<'info>,
 
@@ -48,46 +83,48 @@ pub fn handler(
     installment_number: u8,
     amount: u64,
     payment_hash: String,
+    payment_ref_format: PaymentRefFormat,
 ) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
     let user_profile = &mut ctx.accounts.user_profile;
     let payment_record = &mut ctx.accounts.payment_record;
+    let program_state = &mut ctx.accounts.program_state;
     let clock = Clock::get()?;
 
     require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
     require!(installment_number > 0 && installment_number <= loan.tenure_months, LoanError::InvalidInstallmentNumber);
     require!(amount > 0, LoanError::InvalidPaymentAmount);
-    require!(payment_hash.len() <= PaymentRecord::MAX_HASH_LEN, LoanError::InvalidStringFormat);
+    require!(payment_ref_format.accepts(&payment_hash), LoanError::InvalidStringFormat);
+    require!(payment_record.loan == Pubkey::default(), LoanError::InstallmentAlreadyPaid);
+    require!(ctx.accounts.installment_state.data_is_empty(), LoanError::PartialPaymentInProgress);
 
-    // Calculate due date for this installment
-    let due_date = loan.start_timestamp + ((installment_number as i64) * 30 * 24 * 60 * 60);
-    let grace_period = 2 * 24 * 60 * 60; // 2 days in seconds
-    let grace_end = due_date + grace_period;
+    loan.check_payment_gap(installment_number, program_state, clock.unix_timestamp)?;
 
-    let on_time = clock.unix_timestamp <= grace_end;
-    let days_late = if !on_time {
-        ((clock.unix_timestamp - grace_end) / (24 * 60 * 60)) as u16
-    } else {
-        0
-    };
+    let LateAssessment { on_time, days_late, fine_amount } =
+        loan.assess_lateness(installment_number, clock.unix_timestamp, program_state)?;
 
-    // Calculate fine if late
-    let fine_amount = if days_late > 0 {
-        let daily_fine_rate = 50; // 0.5% per day
-        let fine = (loan.monthly_installment as u128)
-            .checked_mul(daily_fine_rate as u128)
-            .ok_or(LoanError::MathOverflow)?
-            .checked_mul(days_late as u128)
-            .ok_or(LoanError::MathOverflow)?
-            / 10000;
-        fine as u64
-    } else {
-        0
-    };
-
-    let total_due = loan.monthly_installment + fine_amount;
+    // The servicing fee is a flat charge on every installment, on time or not
+    let servicing_fee = loan.servicing_fee_per_installment;
+    let step_schedule = ctx.accounts.step_schedule.as_deref();
+    let scheduled_due = loan.scheduled_due(installment_number, step_schedule)?;
+    let total_due = scheduled_due
+        .checked_add(fine_amount)
+        .and_then(|due| due.checked_add(servicing_fee))
+        .ok_or(LoanError::MathOverflow)?;
     require!(amount >= total_due, LoanError::InsufficientPayment);
 
+    let settled_on_chain = fine_amount.checked_add(servicing_fee)
+        .ok_or(LoanError::MathOverflow)?;
+    deposit_to_treasury(
+        program_state,
+        ctx.accounts.treasury.as_mut(),
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        loan.key(),
+        settled_on_chain,
+        clock.unix_timestamp,
+    )?;
+
     // Record payment
     payment_record.loan = loan.key();
     payment_record.user = ctx.accounts.user.key();
@@ -96,34 +133,51 @@ pub fn handler(
     payment_record.fine_amount = fine_amount;
     payment_record.payment_timestamp = clock.unix_timestamp;
     payment_record.payment_hash = payment_hash;
+    payment_record.payment_ref_format = payment_ref_format;
     payment_record.on_time = on_time;
     payment_record.days_late = days_late;
+    payment_record.fine_waived = 0;
+    payment_record.rent_payer = ctx.accounts.payer.key();
+    payment_record.balance_applied = scheduled_due.min(loan.outstanding_balance);
+    payment_record.carried_fine = if installment_number == loan.schedule_offset + 1 {
+        loan.carried_fine.min(fine_amount)
+    } else {
+        0
+    };
+    payment_record.previous_reversible = loan.reversible_installment;
     payment_record.bump = ctx.bumps.payment_record;
 
     // Update loan
     loan.total_repaid = loan.total_repaid.checked_add(amount)
         .ok_or(LoanError::MathOverflow)?;
-    loan.outstanding_balance = loan.outstanding_balance.saturating_sub(amount);
+    // Only the scheduled installment retires balance; fines and any excess don't pull completion forward
+    loan.outstanding_balance = loan.outstanding_balance.saturating_sub(scheduled_due);
     loan.total_fines = loan.total_fines.checked_add(fine_amount)
         .ok_or(LoanError::MathOverflow)?;
+    loan.total_servicing_fees = loan.total_servicing_fees.checked_add(servicing_fee)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.installments_paid = loan.installments_paid.checked_add(1)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.paid_installments |= 1u64 << (installment_number - 1);
+    loan.reversible_installment = installment_number;
+    // Whatever the liability cap kept off this fine isn't owed any more either
+    if installment_number == loan.schedule_offset + 1 {
+        loan.carried_fine = 0;
+    }
+    loan.last_payment_at = clock.unix_timestamp;
+    loan.last_payment_installment = installment_number;
+    book_installment_split(loan, installment_number, step_schedule, clock.unix_timestamp)?;
+
+    cure_if_caught_up(loan, program_state, clock.unix_timestamp);
 
     // Update user profile
     user_profile.total_repaid = user_profile.total_repaid.checked_add(amount)
         .ok_or(LoanError::MathOverflow)?;
     
-    if on_time {
-        user_profile.on_time_payments = user_profile.on_time_payments.checked_add(1)
-            .ok_or(LoanError::MathOverflow)?;
-        // Improve credit score for on-time payment
-        user_profile.credit_score = user_profile.credit_score.saturating_add(2).min(850);
-    } else {
-        user_profile.late_payments = user_profile.late_payments.checked_add(1)
-            .ok_or(LoanError::MathOverflow)?;
-        // Reduce credit score for late payment
-        user_profile.credit_score = user_profile.credit_score.saturating_sub(5).max(300);
-    }
-
+    let early = loan.is_early(installment_number, clock.unix_timestamp);
+    payment_record.score_change = credit_installment(user_profile, on_time, early, program_state, clock.unix_timestamp)? as i16;
     user_profile.last_updated = clock.unix_timestamp;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
 
     emit!(PaymentRecorded {
         loan: loan.key(),
@@ -131,6 +185,7 @@ pub fn handler(
         installment_number,
         amount,
         fine_amount,
+        servicing_fee,
         payment_timestamp: clock.unix_timestamp,
         on_time,
         days_late,
@@ -141,3 +196,145 @@ pub fn handler(
 
     Ok(())
 }
+
+/// Move fines and servicing fees from the payer into the treasury when they are settled on-chain;
+/// shared by full and partial payments
+pub(crate) fn deposit_to_treasury<'info>(
+    program_state: &LoanProgramState,
+    treasury: Option<&mut Account<'info, Treasury>>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    loan: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    if !program_state.settle_fines_on_chain || amount == 0 {
+        return Ok(());
+    }
+    let treasury = treasury.ok_or(LoanError::TreasuryRequired)?;
+    transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    treasury.total_deposited = treasury.total_deposited.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+
+    emit!(TreasuryDeposited {
+        loan,
+        payer: payer.key(),
+        amount,
+        total_deposited: treasury.total_deposited,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Book a settled installment's scheduled principal and interest on the loan.
+/// Installments before a restructure's offset were booked on the old schedule.
+pub(crate) fn book_installment_split(
+    loan: &mut Account<Loan>,
+    installment_number: u8,
+    step_schedule: Option<&StepSchedule>,
+    now: i64,
+) -> Result<()> {
+    if installment_number <= loan.schedule_offset {
+        return Ok(());
+    }
+    let (principal_portion, interest_portion) = loan.installment_split(installment_number, step_schedule)?;
+    loan.principal_repaid = loan.principal_repaid.checked_add(principal_portion)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.interest_repaid = loan.interest_repaid.checked_add(interest_portion)
+        .ok_or(LoanError::MathOverflow)?;
+
+    emit!(PaymentSplit {
+        loan: loan.key(),
+        installment_number,
+        principal_portion,
+        interest_portion,
+        principal_repaid: loan.principal_repaid,
+        interest_repaid: loan.interest_repaid,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Catching up to within npl_days cures a non-performing loan
+pub(crate) fn cure_if_caught_up(loan: &mut Account<Loan>, program_state: &LoanProgramState, now: i64) {
+    if !loan.non_performing {
+        return;
+    }
+    let days_past_due = loan.days_past_due(now);
+    if days_past_due < program_state.npl_days as i64 {
+        loan.non_performing = false;
+
+        emit!(LoanCured {
+            loan: loan.key(),
+            user: loan.user,
+            days_past_due,
+            timestamp: now,
+        });
+    }
+}
+
+/// Count a settled installment as on time or late and move the credit score to match;
+/// returns the score change actually applied
+pub(crate) fn credit_installment(
+    user_profile: &mut UserProfile,
+    on_time: bool,
+    early: bool,
+    program_state: &LoanProgramState,
+    now: i64,
+) -> Result<i32> {
+    let mut applied = 0;
+    if on_time {
+        user_profile.on_time_payments = user_profile.on_time_payments.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+        // Improve credit score for on-time payment
+        applied += user_profile.adjust_credit_score(LoanProgramState::ON_TIME_PAYMENT_REWARD as i32, program_state, now);
+
+        if early {
+            user_profile.early_payments = user_profile.early_payments.checked_add(1)
+                .ok_or(LoanError::MathOverflow)?;
+            if program_state.early_payment_bonus > 0 {
+                applied += user_profile.adjust_credit_score(program_state.early_payment_bonus as i32, program_state, now);
+            }
+        }
+    } else {
+        user_profile.late_payments = user_profile.late_payments.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+        // Reduce credit score for late payment
+        applied += user_profile.adjust_credit_score(-(LoanProgramState::LATE_PAYMENT_PENALTY as i32), program_state, now);
+    }
+
+    user_profile.sync_risk_level(program_state);
+    Ok(applied)
+}
+
+/// Undo credit_installment for a payment that is being reversed, taking back
+/// exactly the score change it recorded
+pub(crate) fn uncredit_installment(
+    user_profile: &mut UserProfile,
+    payment_record: &PaymentRecord,
+    early: bool,
+    program_state: &LoanProgramState,
+) {
+    if payment_record.on_time {
+        user_profile.on_time_payments = user_profile.on_time_payments.saturating_sub(1);
+        if early {
+            user_profile.early_payments = user_profile.early_payments.saturating_sub(1);
+        }
+    } else {
+        user_profile.late_payments = user_profile.late_payments.saturating_sub(1);
+    }
+    user_profile.revert_score_change(payment_record.score_change as i32, payment_record.payment_timestamp);
+
+    user_profile.sync_risk_level(program_state);
+}
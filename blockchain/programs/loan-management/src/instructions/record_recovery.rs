@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, RecoveryRecorded};
+
+/// Score credit for a good-faith repayment after default
+pub const RECOVERY_SCORE_RESTORE: u16 = 10;
+
+#[derive(Accounts)]
+pub struct RecordRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RecordRecovery>, recovered_amount: u64, restore_score: bool) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Defaulted, LoanError::InvalidStatusTransition);
+    require!(recovered_amount > 0, LoanError::InvalidPaymentAmount);
+
+    // Recoveries can't exceed what was written off and not already covered by insurance
+    let loan_total_recovered = loan.total_recovered.checked_add(recovered_amount)
+        .ok_or(LoanError::MathOverflow)?;
+    require!(
+        loan_total_recovered <= loan.outstanding_balance.saturating_sub(loan.insurance_claimed),
+        LoanError::RecoveryExceedsBalance
+    );
+
+    loan.total_recovered = loan_total_recovered;
+    program_state.total_recovered = program_state.total_recovered.checked_add(recovered_amount)
+        .ok_or(LoanError::MathOverflow)?;
+    program_state.defaulted_outstanding = program_state.defaulted_outstanding.saturating_sub(recovered_amount);
+
+    if restore_score {
+        user_profile.adjust_credit_score(RECOVERY_SCORE_RESTORE as i32, program_state, clock.unix_timestamp);
+        user_profile.last_updated = clock.unix_timestamp;
+    }
+
+    emit!(RecoveryRecorded {
+        loan: loan.key(),
+        user: loan.user,
+        recovered_amount,
+        loan_total_recovered,
+        program_total_recovered: program_state.total_recovered,
+        credit_score: user_profile.credit_score,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::RecordRecovery, loan.key(), clock.unix_timestamp);
+
+    msg!("Recovered {} on defaulted loan {}", recovered_amount, loan.loan_id);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, RecommendationRefreshed, RecommendationZeroed};
+
+#[derive(Accounts)]
+pub struct RefreshLoanRecommendation<'info> {
+    #[account(
+        mut,
+        seeds = [b"user-profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"risk-profile", user.key().as_ref()],
+        bump = risk_profile.bump
+    )]
+    pub risk_profile: Account<'info, RiskProfile>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// CHECK: User authority
+    pub user: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RefreshLoanRecommendation>) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    let risk_profile = &mut ctx.accounts.risk_profile;
+    let clock = Clock::get()?;
+
+    let old_recommendation = risk_profile.recommended_max_loan;
+    let was_eligible = risk_profile.loan_eligible;
+    let min_income = ctx.accounts.program_state.min_income_for_recommendation;
+
+    // Only the transition into ineligibility escalates, so repeated refreshes don't compound it
+    if risk_profile.apply_recommendation(user_profile.monthly_income, &ctx.accounts.program_state)? && was_eligible {
+        risk_profile.risk_level = risk_profile.risk_level.escalated();
+        user_profile.risk_level = risk_profile.risk_level.clone();
+
+        emit!(RecommendationZeroed {
+            user: ctx.accounts.user.key(),
+            monthly_income: user_profile.monthly_income,
+            min_income_for_recommendation: min_income,
+            risk_level: risk_profile.risk_level.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+    }
+    risk_profile.last_calculated = clock.unix_timestamp;
+
+    emit!(RecommendationRefreshed {
+        user: ctx.accounts.user.key(),
+        old_recommended_max_loan: old_recommendation,
+        new_recommended_max_loan: risk_profile.recommended_max_loan,
+        monthly_income: user_profile.monthly_income,
+        risk_level: risk_profile.risk_level.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::RefreshLoanRecommendation, ctx.accounts.user.key(), clock.unix_timestamp);
+
+    msg!("Recommendation refreshed: {} -> {}", old_recommendation, risk_profile.recommended_max_loan);
+
+    Ok(())
+}
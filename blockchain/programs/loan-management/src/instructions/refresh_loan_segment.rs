@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RefreshLoanSegment<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+}
+
+/// Permissionless crank: a borrower's risk level can move without touching
+/// their loans (score updates, missed installments), so this re-counts a loan
+/// under the level it has now rather than waiting for its next payment
+pub fn handler(ctx: Context<RefreshLoanSegment>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let risk_level = &ctx.accounts.user_profile.risk_level;
+
+    ctx.accounts.program_state.sync_loan_segment(loan, risk_level);
+
+    msg!("Loan {} segment refreshed: {:?}", loan.loan_id, loan.risk_segment);
+
+    Ok(())
+}
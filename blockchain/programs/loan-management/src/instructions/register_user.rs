@@ -37,10 +37,39 @@ pub fn handler(
     require!(full_name.len() <= UserProfile::MAX_NAME_LEN, LoanError::NameTooLong);
     require!(monthly_income > 0, LoanError::IncomeTooLow);
 
-    let user_profile = &mut ctx.accounts.user_profile;
     let clock = Clock::get()?;
+    init_profile(
+        &mut ctx.accounts.user_profile,
+        &mut ctx.accounts.program_state,
+        ctx.accounts.authority.key(),
+        ctx.accounts.authority.key(),
+        full_name,
+        monthly_income,
+        employment_type,
+        ctx.bumps.user_profile,
+        clock.unix_timestamp,
+    )
+}
+
+/// Populate a fresh profile and count the new user; shared by key and wallet registration
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn init_profile(
+    user_profile: &mut UserProfile,
+    program_state: &mut LoanProgramState,
+    authority: Pubkey,
+    rent_payer: Pubkey,
+    full_name: String,
+    monthly_income: u64,
+    employment_type: EmploymentType,
+    bump: u8,
+    now: i64,
+) -> Result<()> {
+    require!(
+        program_state.max_users == 0 || program_state.total_users < program_state.max_users,
+        LoanError::UserCapReached
+    );
 
-    user_profile.authority = ctx.accounts.authority.key();
+    user_profile.authority = authority;
     user_profile.full_name = full_name.clone();
     user_profile.monthly_income = monthly_income;
     user_profile.employment_type = employment_type.clone();
@@ -54,22 +83,37 @@ pub fn handler(
     user_profile.late_payments = 0;
     user_profile.missed_payments = 0;
     user_profile.credit_score = 500; // Starting credit score
-    user_profile.risk_level = RiskLevel::Medium;
-    user_profile.registration_timestamp = clock.unix_timestamp;
-    user_profile.last_updated = clock.unix_timestamp;
-    user_profile.bump = ctx.bumps.user_profile;
+    user_profile.registration_timestamp = now;
+    user_profile.last_updated = now;
+    user_profile.monthly_obligations = 0;
+    user_profile.education_completed = false;
+    user_profile.contact_hash = [0u8; 32];
+    user_profile.notification_opt_in = false;
+    user_profile.rent_payer = rent_payer;
+    user_profile.income_change_count = 0;
+    user_profile.income_change_window_start = now;
+    user_profile.co_authority = None;
+    user_profile.score_window_start = now;
+    user_profile.score_window_gain = 0;
+    user_profile.score_window_loss = 0;
+    user_profile.early_payments = 0;
+    user_profile.last_completion_at = 0;
+    user_profile.last_completion_bonus = 0;
+    user_profile.guaranteed_obligations = 0;
+    user_profile.guaranteed_active_loans = 0;
+    user_profile.bump = bump;
+    user_profile.sync_risk_level(program_state);
 
     // Update program state
-    let program_state = &mut ctx.accounts.program_state;
     program_state.total_users = program_state.total_users.checked_add(1)
         .ok_or(LoanError::MathOverflow)?;
 
     emit!(UserRegistered {
-        user: ctx.accounts.authority.key(),
+        user: authority,
         full_name,
         monthly_income,
         employment_type,
-        timestamp: clock.unix_timestamp,
+        timestamp: now,
     });
 
     msg!("User registered: {}", user_profile.full_name);
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::instructions::register_user::init_profile;
+
+#[derive(Accounts)]
+pub struct RegisterWalletUser<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = UserProfile::LEN,
+        seeds = [b"user-profile", authority.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Smart-wallet PDA; only its owner program can produce this signature via invoke_signed
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<RegisterWalletUser>,
+    full_name: String,
+    monthly_income: u64,
+    employment_type: EmploymentType,
+    wallet_seeds: Vec<Vec<u8>>,
+    wallet_bump: u8,
+) -> Result<()> {
+    let wallet_program = ctx.accounts.program_state.borrower_wallet_program;
+    require!(wallet_program != Pubkey::default(), LoanError::WalletBorrowersDisabled);
+    require!(!ctx.accounts.program_state.paused, LoanError::ProgramPaused);
+    require!(full_name.len() <= UserProfile::MAX_NAME_LEN, LoanError::NameTooLong);
+    require!(monthly_income > 0, LoanError::IncomeTooLow);
+
+    // The authority must be a PDA of the approved wallet program, not an arbitrary key
+    let bump_seed = [wallet_bump];
+    let mut seeds: Vec<&[u8]> = wallet_seeds.iter().map(|seed| seed.as_slice()).collect();
+    seeds.push(&bump_seed);
+    let derived = Pubkey::create_program_address(&seeds, &wallet_program)
+        .map_err(|_| LoanError::WalletAuthorityMismatch)?;
+    require!(derived == ctx.accounts.authority.key(), LoanError::WalletAuthorityMismatch);
+
+    let clock = Clock::get()?;
+    init_profile(
+        &mut ctx.accounts.user_profile,
+        &mut ctx.accounts.program_state,
+        ctx.accounts.authority.key(),
+        ctx.accounts.payer.key(),
+        full_name,
+        monthly_income,
+        employment_type,
+        ctx.bumps.user_profile,
+        clock.unix_timestamp,
+    )
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, CollateralReleased};
+
+#[derive(Accounts)]
+pub struct ReleaseCollateral<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral", loan.key().as_ref()],
+        bump = collateral.bump,
+        has_one = loan
+    )]
+    pub collateral: Account<'info, Collateral>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ReleaseCollateral>) -> Result<()> {
+    let loan = &ctx.accounts.loan;
+    let collateral = &mut ctx.accounts.collateral;
+    let clock = Clock::get()?;
+
+    require!(
+        matches!(loan.status, LoanStatus::Completed | LoanStatus::Cancelled) && collateral.is_held(),
+        LoanError::CollateralNotReleasable
+    );
+
+    collateral.locked = false;
+
+    emit!(CollateralReleased {
+        loan: loan.key(),
+        user: loan.user,
+        collateral_type: collateral.collateral_type.clone(),
+        declared_value: collateral.declared_value,
+        released_by: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::ReleaseCollateral, loan.key(), clock.unix_timestamp);
+
+    msg!("Collateral released for loan {}", loan.loan_id);
+
+    Ok(())
+}
+
+/// Closing a secured loan would strand collateral still held for the borrower, so
+/// the close paths take its account and require it released (or seized) first
+pub(crate) fn require_collateral_cleared(loan: &Loan, collateral: Option<&Collateral>) -> Result<()> {
+    require!(loan.is_secured == collateral.is_some(), LoanError::CollateralAccountMismatch);
+    if let Some(collateral) = collateral {
+        require!(!collateral.is_held(), LoanError::CollateralStillLocked);
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, LoanRestructured};
+use crate::instructions::create_loan::{amortize, Schedule};
+use crate::instructions::record_payment::cure_if_caught_up;
+
+#[derive(Accounts)]
+pub struct RestructureLoan<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Part-payment tracker for the first unpaid installment; must be empty
+    /// so no part payment is stranded against the old schedule. Paid installments
+    /// must be contiguous, so no earlier installment can have one
+    #[account(
+        seeds = [
+            b"installment",
+            loan.key().as_ref(),
+            &loan.installments_paid.saturating_add(1).to_le_bytes()
+        ],
+        bump
+    )]
+    pub installment_state: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub admin: Signer<'info>,
+}
+
+/// `new_tenure_months` counts the installments in the new schedule; numbering
+/// carries on from the first unpaid installment so existing records stay valid.
+/// Lateness restarts against the new schedule, and `fine_handling` decides what
+/// happens to the fines accrued on the old one.
+pub fn handler(
+    ctx: Context<RestructureLoan>,
+    new_tenure_months: Option<u8>,
+    new_interest_rate: Option<u16>,
+    fine_handling: RestructureFineHandling,
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(loan.status == LoanStatus::Active, LoanError::LoanNotActive);
+    require!(loan.outstanding_balance > 0, LoanError::LoanAlreadyCompleted);
+    require!(
+        new_tenure_months.is_some() || new_interest_rate.is_some(),
+        LoanError::NothingToRestructure
+    );
+    require!(
+        loan.restructure_count < program_state.max_restructures_per_loan,
+        LoanError::RestructureLimitReached
+    );
+    require!(ctx.accounts.installment_state.data_is_empty(), LoanError::PartialPaymentInProgress);
+
+    let offset = loan.installments_paid;
+    // A gap would leave an older installment unpaid and renumber the new schedule onto a paid one
+    require!(loan.paid_installments == (1u64 << offset) - 1, LoanError::PaymentsOutOfOrder);
+    let remaining = new_tenure_months.unwrap_or(loan.tenure_months.saturating_sub(offset));
+    require!(
+        (3..=60).contains(&remaining) && (offset as u16) + (remaining as u16) <= 60,
+        LoanError::InvalidTenure
+    );
+
+    let interest_rate = new_interest_rate.unwrap_or(loan.interest_rate);
+    require!(
+        interest_rate > 0 && interest_rate <= LoanProgramState::MAX_INTEREST_RATE,
+        LoanError::InvalidInterestRate
+    );
+
+    // Assessed on the old schedule, before the new one moves every due date out
+    let accrued_fines = loan.accrued_fines(clock.unix_timestamp, program_state)?;
+    let (carried_fine, capitalized_fines) = match fine_handling {
+        RestructureFineHandling::CarryForward => (accrued_fines, 0),
+        RestructureFineHandling::Waive => (0, 0),
+        RestructureFineHandling::Capitalize => (0, accrued_fines),
+    };
+
+    // The whole outstanding balance becomes the principal of the new schedule
+    let restructured_principal = loan.outstanding_balance.checked_add(capitalized_fines)
+        .ok_or(LoanError::MathOverflow)?;
+    let Schedule { monthly_installment, final_installment, total_amount } =
+        amortize(restructured_principal, interest_rate, remaining, program_state)?;
+
+    let old_installment = loan.monthly_installment;
+    let old_tenure = loan.tenure_months;
+    let old_interest_rate = loan.interest_rate;
+
+    loan.principal_amount = restructured_principal;
+    loan.interest_rate = interest_rate;
+    loan.tenure_months = offset + remaining;
+    loan.monthly_installment = monthly_installment;
+    loan.final_installment = final_installment;
+    // The new level schedule replaces any stepped plan
+    loan.is_stepped = false;
+    loan.total_amount = total_amount;
+    loan.outstanding_balance = total_amount;
+    // The split totals restart against the new principal
+    loan.principal_repaid = 0;
+    loan.interest_repaid = 0;
+    // Back-date the start so the first unpaid installment falls due one period from now
+    loan.start_timestamp = clock.unix_timestamp - (offset as i64) * SECONDS_PER_PERIOD;
+    loan.end_timestamp = loan.start_timestamp + (loan.tenure_months as i64) * SECONDS_PER_PERIOD;
+    loan.schedule_offset = offset;
+    // Misses on rescheduled installments no longer apply
+    loan.missed_installments &= (1u64 << offset) - 1;
+    loan.carried_fine = carried_fine;
+    // Payments on the old schedule can no longer be unwound
+    loan.reversible_installment = 0;
+    loan.restructure_count += 1;
+    // Nothing is overdue against the new schedule yet
+    cure_if_caught_up(loan, program_state, clock.unix_timestamp);
+
+    user_profile.monthly_obligations = user_profile.monthly_obligations
+        .saturating_sub(loan.monthly_obligation)
+        .saturating_add(monthly_installment);
+    loan.monthly_obligation = monthly_installment;
+    user_profile.last_updated = clock.unix_timestamp;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    emit!(LoanRestructured {
+        loan_id: loan.loan_id,
+        user: loan.user,
+        old_installment,
+        new_installment: monthly_installment,
+        old_tenure,
+        new_tenure: loan.tenure_months,
+        old_interest_rate,
+        new_interest_rate: interest_rate,
+        restructured_principal,
+        fine_handling,
+        fines_handled: accrued_fines,
+        restructure_count: loan.restructure_count,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::RestructureLoan, loan.key(), clock.unix_timestamp);
+
+    msg!("Loan {} restructured: {} installments of {}", loan.loan_id, remaining, monthly_installment);
+
+    Ok(())
+}
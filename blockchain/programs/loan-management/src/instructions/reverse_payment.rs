@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, PaymentReversed};
+use crate::instructions::add_guarantor::guarantor_accounts;
+use crate::instructions::record_payment::uncredit_installment;
+
+#[derive(Accounts)]
+#[instruction(installment_number: u8)]
+pub struct ReversePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+
+    #[account(
+        mut,
+        seeds = [b"user-profile", loan.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"payment",
+            loan.key().as_ref(),
+            &installment_number.to_le_bytes()
+        ],
+        bump = payment_record.bump,
+        has_one = rent_payer @ LoanError::InvalidCloseRecord,
+        close = rent_payer
+    )]
+    pub payment_record: Account<'info, PaymentRecord>,
+
+    /// CHECK: Receives the record's rent; must match payment_record.rent_payer
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    /// Required exactly when the loan is stepped
+    #[account(
+        seeds = [b"step-schedule", loan.key().as_ref()],
+        bump = step_schedule.bump
+    )]
+    pub step_schedule: Option<Account<'info, StepSchedule>>,
+
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    /// Required when the loan has a guarantor, so a reopened loan's guarantee counts against them again
+    #[account(
+        seeds = [b"guarantor", loan.key().as_ref()],
+        bump = guarantor.bump,
+        has_one = loan
+    )]
+    pub guarantor: Option<Account<'info, Guarantor>>,
+
+    /// The guarantor's own profile; required alongside guarantor
+    #[account(mut)]
+    pub guarantor_profile: Option<Account<'info, UserProfile>>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Payments are reversed most recent first, whatever order the installments
+/// were paid in; one settled in parts ends the chain. A payment that completed the loan is
+/// reversed only with `reopen`, which puts the loan back to Active and undoes
+/// the completion. Funds already moved off-chain or into the treasury are
+/// refunded outside the program.
+pub fn handler(ctx: Context<ReversePayment>, installment_number: u8, reopen: bool) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let payment_record = &ctx.accounts.payment_record;
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    match loan.status {
+        LoanStatus::Active => {}
+        LoanStatus::Completed => require!(reopen, LoanError::ReopenRequired),
+        _ => return err!(LoanError::LoanNotActive),
+    }
+    require!(installment_number == loan.reversible_installment, LoanError::NotLatestPayment);
+
+    let reopened = loan.status == LoanStatus::Completed;
+    if reopened {
+        loan.validate_status_timestamps()?;
+        loan.status = LoanStatus::Active;
+        loan.completed_timestamp = None;
+        loan.validate_status_timestamps()?;
+
+        user_profile.active_loans = user_profile.active_loans.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+        user_profile.monthly_obligations = user_profile.monthly_obligations.checked_add(loan.monthly_obligation)
+            .ok_or(LoanError::MathOverflow)?;
+        user_profile.completed_loans = user_profile.completed_loans.saturating_sub(1);
+        let (bonus, completed_at) = (user_profile.last_completion_bonus, user_profile.last_completion_at);
+        user_profile.revert_score_change(bonus as i32, completed_at);
+        // The completion no longer stands, so a later default has no bonus to claw back
+        user_profile.last_completion_bonus = 0;
+    }
+    // Only a reopen puts the guarantee back in force; an active loan's guarantee is already counted
+    let guarantee = guarantor_accounts(loan, ctx.accounts.guarantor.as_deref(), ctx.accounts.guarantor_profile.as_deref_mut())?;
+    if let (true, Some((guarantor, guarantor_profile))) = (reopened, guarantee) {
+        guarantor_profile.take_on_guarantee(guarantor)?;
+    }
+
+    loan.outstanding_balance = loan.outstanding_balance.checked_add(payment_record.balance_applied)
+        .ok_or(LoanError::MathOverflow)?;
+    loan.total_repaid = loan.total_repaid.saturating_sub(payment_record.amount);
+    loan.total_fines = loan.total_fines.saturating_sub(payment_record.outstanding_fine());
+    loan.total_servicing_fees = loan.total_servicing_fees.saturating_sub(loan.servicing_fee_per_installment);
+    if installment_number > loan.schedule_offset {
+        let (principal_portion, interest_portion) =
+            loan.installment_split(installment_number, ctx.accounts.step_schedule.as_deref())?;
+        loan.principal_repaid = loan.principal_repaid.saturating_sub(principal_portion);
+        loan.interest_repaid = loan.interest_repaid.saturating_sub(interest_portion);
+    }
+    loan.installments_paid -= 1;
+    loan.paid_installments &= !(1u64 << (installment_number - 1));
+    loan.reversible_installment = payment_record.previous_reversible;
+    loan.carried_fine = loan.carried_fine.checked_add(payment_record.carried_fine)
+        .ok_or(LoanError::MathOverflow)?;
+
+    user_profile.total_repaid = user_profile.total_repaid.saturating_sub(payment_record.amount);
+    let early = loan.is_early(installment_number, payment_record.payment_timestamp);
+    uncredit_installment(user_profile, payment_record, early, program_state);
+    user_profile.last_updated = clock.unix_timestamp;
+    program_state.sync_loan_segment(loan, &user_profile.risk_level);
+
+    emit!(PaymentReversed {
+        loan: loan.key(),
+        user: loan.user,
+        installment_number,
+        amount: payment_record.amount,
+        fine_amount: payment_record.fine_amount,
+        balance_restored: payment_record.balance_applied,
+        reopened,
+        reversed_by: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::ReversePayment, loan.key(), clock.unix_timestamp);
+
+    msg!("Payment reversed: Loan={}, Installment={}, Amount={}",
+        loan.loan_id, installment_number, payment_record.amount);
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::CoAuthorityUpdated;
+
+#[derive(Accounts)]
+pub struct SetCoAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"user-profile", authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetCoAuthority>, co_authority: Option<Pubkey>) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    let clock = Clock::get()?;
+
+    // Only the primary authority manages the joint holder, and can't name itself
+    require!(co_authority != Some(user_profile.authority), LoanError::Unauthorized);
+    user_profile.co_authority = co_authority;
+
+    emit!(CoAuthorityUpdated {
+        user: user_profile.authority,
+        co_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Co-authority updated for: {}", user_profile.full_name);
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, EmploymentBlocklistUpdated};
+
+#[derive(Accounts)]
+pub struct SetEmploymentBlocklist<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetEmploymentBlocklist>, blocked_mask: u8) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    require!(blocked_mask & !EmploymentType::ALL_MASK == 0, LoanError::InvalidConfigValue);
+
+    let old_mask = program_state.blocked_employment_types;
+    program_state.blocked_employment_types = blocked_mask;
+
+    emit!(EmploymentBlocklistUpdated {
+        old_mask,
+        new_mask: blocked_mask,
+        authority: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::SetEmploymentBlocklist,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Employment blocklist set to {:#07b}", blocked_mask);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, MaxActiveLoansUpdated};
+
+#[derive(Accounts)]
+pub struct SetMaxActiveLoans<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMaxActiveLoans>, max_active_loans: u8) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    // Zero would stop all lending; pausing the program is the tool for that.
+    // Lowering the cap never touches loans already open.
+    require!(max_active_loans > 0, LoanError::InvalidConfigValue);
+
+    let old_max_active_loans = program_state.max_active_loans;
+    program_state.max_active_loans = max_active_loans;
+
+    emit!(MaxActiveLoansUpdated {
+        old_max_active_loans,
+        new_max_active_loans: max_active_loans,
+        authority: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::SetMaxActiveLoans,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Max active loans per user set to {}", max_active_loans);
+
+    Ok(())
+}
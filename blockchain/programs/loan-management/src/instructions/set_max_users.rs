@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, MaxUsersUpdated};
+
+#[derive(Accounts)]
+pub struct SetMaxUsers<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMaxUsers>, max_users: u64) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    // A cap below the current count would be meaningless; existing users are never evicted
+    require!(
+        max_users == 0 || max_users >= program_state.total_users,
+        LoanError::InvalidConfigValue
+    );
+
+    let old_max_users = program_state.max_users;
+    program_state.max_users = max_users;
+
+    emit!(MaxUsersUpdated {
+        old_max_users,
+        new_max_users: max_users,
+        total_users: program_state.total_users,
+        authority: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::SetMaxUsers,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Max users set to {}", max_users);
+
+    Ok(())
+}
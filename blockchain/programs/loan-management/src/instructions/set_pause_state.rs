@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, ProgramPauseToggled};
+
+#[derive(Accounts)]
+pub struct SetPauseState<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetPauseState>, paused: bool) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    // Only a real resume restarts the cooldown; re-sending false leaves it alone
+    if program_state.paused && !paused {
+        program_state.unpaused_at = clock.unix_timestamp;
+    }
+    program_state.paused = paused;
+    let cooldown_ends_at = if paused { 0 } else { program_state.post_unpause_cooldown_ends_at() };
+
+    emit!(ProgramPauseToggled {
+        paused,
+        cooldown_ends_at,
+        authority: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::SetPauseState,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Program {}", if paused { "paused" } else { "resumed" });
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, AuthorityTransferProposed};
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    // Only proposed here; a mistyped key can't take over because it can never accept.
+    // Pubkey::default() withdraws a pending proposal.
+    program_state.pending_authority = new_authority;
+
+    emit!(AuthorityTransferProposed {
+        authority: ctx.accounts.authority.key(),
+        pending_authority: new_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::TransferAuthority,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Authority transfer proposed to {}", new_authority);
+
+    Ok(())
+}
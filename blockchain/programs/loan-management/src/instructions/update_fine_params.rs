@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, FineParamsUpdated};
+
+#[derive(Accounts)]
+pub struct UpdateFineParams<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateFineParams>,
+    grace_period_days: Option<u8>,
+    daily_fine_bps: Option<u16>,
+) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+    let clock = Clock::get()?;
+
+    if let Some(days) = grace_period_days {
+        // Grace has to close before the next installment falls due
+        require!((days as i64) * SECONDS_PER_DAY < SECONDS_PER_PERIOD, LoanError::InvalidConfigValue);
+        program_state.grace_period_days = days;
+    }
+
+    if let Some(bps) = daily_fine_bps {
+        require!(bps as u64 <= MAX_FINE_BPS, LoanError::InvalidConfigValue);
+        program_state.daily_fine_bps = bps;
+    }
+
+    emit!(FineParamsUpdated {
+        grace_period_days: program_state.grace_period_days,
+        daily_fine_bps: program_state.daily_fine_bps,
+        authority: ctx.accounts.authority.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::UpdateFineParams,
+        program_state.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Fine params updated: grace={} days, daily={} bps",
+        program_state.grace_period_days, program_state.daily_fine_bps);
+
+    Ok(())
+}
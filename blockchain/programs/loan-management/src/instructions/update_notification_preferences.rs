@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::NotificationPreferencesUpdated;
+
+#[derive(Accounts)]
+pub struct UpdateNotificationPreferences<'info> {
+    #[account(
+        mut,
+        seeds = [b"user-profile", authority.key().as_ref()],
+        bump = user_profile.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateNotificationPreferences>,
+    contact_hash: [u8; 32],
+    notification_opt_in: bool,
+) -> Result<()> {
+    let user_profile = &mut ctx.accounts.user_profile;
+    let clock = Clock::get()?;
+
+    // Deliberately leaves last_updated alone: preferences aren't an underwriting refresh
+    user_profile.contact_hash = contact_hash;
+    user_profile.notification_opt_in = notification_opt_in;
+
+    emit!(NotificationPreferencesUpdated {
+        user: user_profile.authority,
+        contact_hash,
+        notification_opt_in,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Notification preferences updated for: {}", user_profile.full_name);
+
+    Ok(())
+}
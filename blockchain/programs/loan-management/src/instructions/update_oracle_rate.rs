@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::OracleRateUpdated;
+
+#[derive(Accounts)]
+pub struct UpdateOracleRate<'info> {
+    #[account(
+        mut,
+        seeds = [b"rate-oracle"],
+        bump = rate_oracle.bump,
+        has_one = feeder @ LoanError::Unauthorized
+    )]
+    pub rate_oracle: Account<'info, RateOracle>,
+
+    pub feeder: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateOracleRate>, rate_bps: u16) -> Result<()> {
+    require!(rate_bps <= LoanProgramState::MAX_INTEREST_RATE, LoanError::InvalidInterestRate);
+
+    let rate_oracle = &mut ctx.accounts.rate_oracle;
+    let clock = Clock::get()?;
+    let previous_rate_bps = rate_oracle.current_rate_bps;
+
+    rate_oracle.current_rate_bps = rate_bps;
+    rate_oracle.updated_at = clock.unix_timestamp;
+
+    emit!(OracleRateUpdated {
+        oracle: rate_oracle.key(),
+        previous_rate_bps,
+        current_rate_bps: rate_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Oracle rate updated: {} -> {} bps", previous_rate_bps, rate_bps);
+
+    Ok(())
+}
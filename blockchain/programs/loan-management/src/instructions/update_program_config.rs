@@ -0,0 +1,437 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::emit_admin_action;
+
+/// Tunable program parameters; `None` leaves the current value untouched
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ProgramConfigParams {
+    pub max_dti_bps: Option<u16>,
+    pub escalate_risk_on_dti_breach: Option<bool>,
+    pub allow_neg_am: Option<bool>,
+    pub anomaly_detection_enabled: Option<bool>,
+    pub anomaly_window_seconds: Option<i64>,
+    pub anomaly_max_loans: Option<u32>,
+    pub anomaly_max_volume: Option<u64>,
+    pub first_loan_fine_discount_bps: Option<u16>,
+    pub min_payment_gap_seconds: Option<i64>,
+    pub rate_bands: Option<[RateBand; 4]>,
+    pub max_profile_age_for_loan: Option<i64>,
+    pub completion_bonus: Option<u16>,
+    pub default_penalty: Option<u16>,
+    /// Pubkey::default() turns wallet borrowers off again
+    pub borrower_wallet_program: Option<Pubkey>,
+    pub insurance_premium_bps: Option<u16>,
+    pub discount_score_threshold: Option<u16>,
+    pub score_discount_bps: Option<u16>,
+    pub discount_rate_floor: Option<u16>,
+    pub reminder_ladder_offsets: Option<[i64; 5]>,
+    /// Pubkey::default() returns to fixed-rate origination
+    pub rate_oracle: Option<Pubkey>,
+    pub oracle_spread_bps: Option<u16>,
+    pub education_score_boost: Option<u16>,
+    pub grace_after_maturity_days: Option<u16>,
+    pub max_total_repayment_multiplier_bps: Option<u16>,
+    pub min_income_for_recommendation: Option<u64>,
+    pub min_spread_bps: Option<u16>,
+    pub max_spread_bps: Option<u16>,
+    pub risk_level_cutoffs: Option<[u16; 3]>,
+    pub min_installment_amount: Option<u64>,
+    pub npl_days: Option<u16>,
+    pub npl_penalty: Option<u16>,
+    pub completion_dust_tolerance: Option<u64>,
+    pub max_income_changes_per_window: Option<u8>,
+    pub income_change_window_seconds: Option<i64>,
+    pub reject_excess_income_changes: Option<bool>,
+    pub min_account_age_for_loan: Option<i64>,
+    pub missed_payment_penalty: Option<u16>,
+    pub retention_period_seconds: Option<i64>,
+    pub prepayment_rebate_bps: Option<u16>,
+    pub final_installment_grace_days: Option<u8>,
+    pub borrower_friendly_rounding: Option<bool>,
+    pub max_loans_per_slot: Option<u16>,
+    pub guarantor_default_penalty: Option<u16>,
+    pub allow_fine_only_payments: Option<bool>,
+    pub max_score_change_per_window: Option<u16>,
+    pub score_change_window_seconds: Option<i64>,
+    pub require_risk_profile: Option<bool>,
+    pub max_restructures_per_loan: Option<u8>,
+    pub fine_cap_bps: Option<u16>,
+    pub min_fine_cap: Option<u64>,
+    pub early_payment_bonus: Option<u16>,
+    pub max_default_probability_for_loan: Option<u16>,
+    pub settle_fines_on_chain: Option<bool>,
+    pub servicing_fee_per_installment: Option<u64>,
+    pub credit_line_min_completed_loans: Option<u8>,
+    pub bonus_clawback_window_seconds: Option<i64>,
+    pub max_start_backdate_seconds: Option<i64>,
+    pub max_start_lead_seconds: Option<i64>,
+    pub guarantee_dti_weight_bps: Option<u16>,
+    pub max_guarantees: Option<u8>,
+    pub fee_refund_window_days: Option<u16>,
+    pub post_unpause_cooldown_seconds: Option<i64>,
+    pub payment_waterfall: Option<PaymentWaterfall>,
+    pub missed_installments_to_default: Option<u8>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateProgramConfig>, params: ProgramConfigParams) -> Result<()> {
+    let program_state = &mut ctx.accounts.program_state;
+
+    if let Some(max_dti_bps) = params.max_dti_bps {
+        require!(max_dti_bps > 0, LoanError::InvalidConfigValue);
+        program_state.max_dti_bps = max_dti_bps;
+    }
+
+    if let Some(escalate) = params.escalate_risk_on_dti_breach {
+        program_state.escalate_risk_on_dti_breach = escalate;
+    }
+
+    if let Some(allow_neg_am) = params.allow_neg_am {
+        program_state.allow_neg_am = allow_neg_am;
+    }
+
+    if let Some(window) = params.anomaly_window_seconds {
+        require!(window > 0, LoanError::InvalidConfigValue);
+        program_state.anomaly_window_seconds = window;
+    }
+
+    if let Some(max_loans) = params.anomaly_max_loans {
+        program_state.anomaly_max_loans = max_loans;
+    }
+
+    if let Some(max_volume) = params.anomaly_max_volume {
+        program_state.anomaly_max_volume = max_volume;
+    }
+
+    if let Some(enabled) = params.anomaly_detection_enabled {
+        require!(
+            !enabled || program_state.anomaly_window_seconds > 0,
+            LoanError::InvalidConfigValue
+        );
+        program_state.anomaly_detection_enabled = enabled;
+    }
+
+    if let Some(discount) = params.first_loan_fine_discount_bps {
+        require!(discount <= 10000, LoanError::InvalidConfigValue);
+        program_state.first_loan_fine_discount_bps = discount;
+    }
+
+    if let Some(gap) = params.min_payment_gap_seconds {
+        require!(gap >= 0, LoanError::InvalidConfigValue);
+        program_state.min_payment_gap_seconds = gap;
+    }
+
+    if let Some(bands) = params.rate_bands {
+        require!(bands[0].min_score == 0, LoanError::InvalidConfigValue);
+        for (i, band) in bands.iter().enumerate() {
+            require!(
+                band.min_rate > 0
+                    && band.min_rate <= band.max_rate
+                    && band.max_rate <= LoanProgramState::MAX_INTEREST_RATE,
+                LoanError::InvalidConfigValue
+            );
+            if i > 0 {
+                require!(band.min_score > bands[i - 1].min_score, LoanError::InvalidConfigValue);
+            }
+        }
+        program_state.rate_bands = bands;
+    }
+
+    if let Some(max_age) = params.max_profile_age_for_loan {
+        require!(max_age > 0, LoanError::InvalidConfigValue);
+        program_state.max_profile_age_for_loan = max_age;
+    }
+
+    let score_range = LoanProgramState::MAX_CREDIT_SCORE - LoanProgramState::MIN_CREDIT_SCORE;
+
+    if let Some(bonus) = params.completion_bonus {
+        require!(bonus <= score_range, LoanError::InvalidConfigValue);
+        program_state.completion_bonus = bonus;
+    }
+
+    if let Some(penalty) = params.default_penalty {
+        require!(penalty <= score_range, LoanError::InvalidConfigValue);
+        program_state.default_penalty = penalty;
+    }
+
+    if let Some(wallet_program) = params.borrower_wallet_program {
+        program_state.borrower_wallet_program = wallet_program;
+    }
+
+    if let Some(premium_bps) = params.insurance_premium_bps {
+        require!(premium_bps <= 10000, LoanError::InvalidConfigValue);
+        program_state.insurance_premium_bps = premium_bps;
+    }
+
+    if let Some(threshold) = params.discount_score_threshold {
+        require!(threshold <= LoanProgramState::MAX_CREDIT_SCORE, LoanError::InvalidConfigValue);
+        program_state.discount_score_threshold = threshold;
+    }
+
+    if let Some(discount) = params.score_discount_bps {
+        require!(discount <= LoanProgramState::MAX_INTEREST_RATE, LoanError::InvalidConfigValue);
+        program_state.score_discount_bps = discount;
+    }
+
+    if let Some(floor) = params.discount_rate_floor {
+        require!(
+            floor > 0 && floor <= LoanProgramState::MAX_INTEREST_RATE,
+            LoanError::InvalidConfigValue
+        );
+        program_state.discount_rate_floor = floor;
+    }
+
+    if let Some(offsets) = params.reminder_ladder_offsets {
+        require!(
+            offsets.windows(2).all(|pair| pair[0] < pair[1]),
+            LoanError::InvalidConfigValue
+        );
+        program_state.reminder_ladder_offsets = offsets;
+    }
+
+    if let Some(rate_oracle) = params.rate_oracle {
+        program_state.rate_oracle = rate_oracle;
+    }
+
+    if let Some(spread) = params.oracle_spread_bps {
+        require!(spread <= LoanProgramState::MAX_INTEREST_RATE, LoanError::InvalidConfigValue);
+        program_state.oracle_spread_bps = spread;
+    }
+
+    if let Some(boost) = params.education_score_boost {
+        require!(boost <= score_range, LoanError::InvalidConfigValue);
+        program_state.education_score_boost = boost;
+    }
+
+    if let Some(grace_days) = params.grace_after_maturity_days {
+        program_state.grace_after_maturity_days = grace_days;
+    }
+
+    if let Some(multiplier) = params.max_total_repayment_multiplier_bps {
+        // Anything below 1x would forbid repaying the principal itself
+        require!(multiplier == 0 || multiplier >= 10000, LoanError::InvalidConfigValue);
+        program_state.max_total_repayment_multiplier_bps = multiplier;
+    }
+
+    if let Some(min_income) = params.min_income_for_recommendation {
+        program_state.min_income_for_recommendation = min_income;
+    }
+
+    if params.min_spread_bps.is_some() || params.max_spread_bps.is_some() {
+        let min_spread = params.min_spread_bps.unwrap_or(program_state.min_spread_bps);
+        let max_spread = params.max_spread_bps.unwrap_or(program_state.max_spread_bps);
+        require!(
+            min_spread <= max_spread && max_spread <= LoanProgramState::MAX_INTEREST_RATE,
+            LoanError::InvalidConfigValue
+        );
+        program_state.min_spread_bps = min_spread;
+        program_state.max_spread_bps = max_spread;
+    }
+
+    if let Some(cutoffs) = params.risk_level_cutoffs {
+        // Strictly descending so every level owns a non-empty score range
+        require!(
+            cutoffs[0] <= LoanProgramState::MAX_CREDIT_SCORE
+                && cutoffs[0] > cutoffs[1]
+                && cutoffs[1] > cutoffs[2]
+                && cutoffs[2] >= LoanProgramState::MIN_CREDIT_SCORE,
+            LoanError::InvalidConfigValue
+        );
+        program_state.risk_level_cutoffs = cutoffs;
+    }
+
+    if let Some(amount) = params.min_installment_amount {
+        program_state.min_installment_amount = amount;
+    }
+
+    if let Some(npl_days) = params.npl_days {
+        require!(npl_days > 0, LoanError::InvalidConfigValue);
+        program_state.npl_days = npl_days;
+    }
+
+    // Non-performing is a softer step than default, so its penalty never exceeds it
+    if params.npl_penalty.is_some() || params.default_penalty.is_some() {
+        let npl_penalty = params.npl_penalty.unwrap_or(program_state.npl_penalty);
+        require!(npl_penalty <= program_state.default_penalty, LoanError::InvalidConfigValue);
+        program_state.npl_penalty = npl_penalty;
+    }
+
+    if let Some(tolerance) = params.completion_dust_tolerance {
+        require!(tolerance <= LoanProgramState::MAX_COMPLETION_DUST_TOLERANCE, LoanError::InvalidConfigValue);
+        program_state.completion_dust_tolerance = tolerance;
+    }
+
+    if let Some(max_changes) = params.max_income_changes_per_window {
+        program_state.max_income_changes_per_window = max_changes;
+    }
+
+    if let Some(window) = params.income_change_window_seconds {
+        require!(window > 0, LoanError::InvalidConfigValue);
+        program_state.income_change_window_seconds = window;
+    }
+
+    if let Some(reject) = params.reject_excess_income_changes {
+        program_state.reject_excess_income_changes = reject;
+    }
+
+    if let Some(min_age) = params.min_account_age_for_loan {
+        require!(min_age >= 0, LoanError::InvalidConfigValue);
+        program_state.min_account_age_for_loan = min_age;
+    }
+
+    if let Some(penalty) = params.missed_payment_penalty {
+        require!(
+            penalty > LoanProgramState::LATE_PAYMENT_PENALTY && penalty <= score_range,
+            LoanError::InvalidConfigValue
+        );
+        program_state.missed_payment_penalty = penalty;
+    }
+
+    if let Some(retention) = params.retention_period_seconds {
+        require!(retention > 0, LoanError::InvalidConfigValue);
+        program_state.retention_period_seconds = retention;
+    }
+
+    if let Some(rebate_bps) = params.prepayment_rebate_bps {
+        require!(rebate_bps <= 10000, LoanError::InvalidConfigValue);
+        program_state.prepayment_rebate_bps = rebate_bps;
+    }
+
+    if let Some(days) = params.final_installment_grace_days {
+        require!((days as i64) * SECONDS_PER_DAY < SECONDS_PER_PERIOD, LoanError::InvalidConfigValue);
+        program_state.final_installment_grace_days = days;
+    }
+
+    if let Some(friendly) = params.borrower_friendly_rounding {
+        program_state.borrower_friendly_rounding = friendly;
+    }
+
+    if let Some(max_per_slot) = params.max_loans_per_slot {
+        program_state.max_loans_per_slot = max_per_slot;
+    }
+
+    if let Some(penalty) = params.guarantor_default_penalty {
+        require!(penalty <= score_range, LoanError::InvalidConfigValue);
+        program_state.guarantor_default_penalty = penalty;
+    }
+
+    if let Some(allow) = params.allow_fine_only_payments {
+        program_state.allow_fine_only_payments = allow;
+    }
+
+    if let Some(max_change) = params.max_score_change_per_window {
+        require!(max_change <= score_range, LoanError::InvalidConfigValue);
+        program_state.max_score_change_per_window = max_change;
+    }
+
+    if let Some(window) = params.score_change_window_seconds {
+        require!(window > 0, LoanError::InvalidConfigValue);
+        program_state.score_change_window_seconds = window;
+    }
+
+    if let Some(required) = params.require_risk_profile {
+        program_state.require_risk_profile = required;
+    }
+
+    if let Some(max_restructures) = params.max_restructures_per_loan {
+        program_state.max_restructures_per_loan = max_restructures;
+    }
+
+    if let Some(cap_bps) = params.fine_cap_bps {
+        require!((1..=10000).contains(&cap_bps), LoanError::InvalidConfigValue);
+        program_state.fine_cap_bps = cap_bps;
+    }
+
+    if let Some(min_cap) = params.min_fine_cap {
+        program_state.min_fine_cap = min_cap;
+    }
+
+    if let Some(bonus) = params.early_payment_bonus {
+        program_state.early_payment_bonus = bonus;
+    }
+
+    if let Some(max_probability) = params.max_default_probability_for_loan {
+        require!(max_probability <= 10000, LoanError::InvalidConfigValue);
+        program_state.max_default_probability_for_loan = max_probability;
+    }
+
+    if let Some(settle) = params.settle_fines_on_chain {
+        program_state.settle_fines_on_chain = settle;
+    }
+
+    if let Some(fee) = params.servicing_fee_per_installment {
+        program_state.servicing_fee_per_installment = fee;
+    }
+
+    if let Some(min_completed) = params.credit_line_min_completed_loans {
+        require!(min_completed > 0, LoanError::InvalidConfigValue);
+        program_state.credit_line_min_completed_loans = min_completed;
+    }
+
+    if let Some(window) = params.bonus_clawback_window_seconds {
+        require!(window >= 0, LoanError::InvalidConfigValue);
+        program_state.bonus_clawback_window_seconds = window;
+    }
+
+    if let Some(backdate) = params.max_start_backdate_seconds {
+        require!(backdate > 0, LoanError::InvalidConfigValue);
+        program_state.max_start_backdate_seconds = backdate;
+    }
+
+    if let Some(lead) = params.max_start_lead_seconds {
+        require!(lead > 0, LoanError::InvalidConfigValue);
+        program_state.max_start_lead_seconds = lead;
+    }
+
+    if let Some(v) = params.guarantee_dti_weight_bps {
+        require!(v <= 10000, LoanError::InvalidConfigValue);
+        program_state.guarantee_dti_weight_bps = v;
+    }
+
+    if let Some(max_guarantees) = params.max_guarantees {
+        program_state.max_guarantees = max_guarantees;
+    }
+
+    if let Some(days) = params.fee_refund_window_days {
+        require!(days <= 90, LoanError::InvalidConfigValue);
+        program_state.fee_refund_window_days = days;
+    }
+
+    if let Some(cooldown) = params.post_unpause_cooldown_seconds {
+        require!(cooldown >= 0, LoanError::InvalidConfigValue);
+        program_state.post_unpause_cooldown_seconds = cooldown;
+    }
+
+    if let Some(waterfall) = params.payment_waterfall {
+        program_state.payment_waterfall = waterfall;
+    }
+
+    if let Some(misses) = params.missed_installments_to_default {
+        require!(misses <= 60, LoanError::InvalidConfigValue);
+        program_state.missed_installments_to_default = misses;
+    }
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::UpdateProgramConfig,
+        program_state.key(),
+        Clock::get()?.unix_timestamp,
+    );
+
+    msg!("Program config updated by {}", ctx.accounts.authority.key());
+
+    Ok(())
+}
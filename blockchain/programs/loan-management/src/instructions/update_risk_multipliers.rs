@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::emit_admin_action;
+
+#[derive(Accounts)]
+pub struct UpdateRiskMultipliers<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateRiskMultipliers>, risk_multipliers: [u16; 4]) -> Result<()> {
+    // Riskier tiers never get more borrowing capacity than safer ones
+    require!(
+        risk_multipliers[0] <= LoanProgramState::MAX_RISK_MULTIPLIER
+            && risk_multipliers.windows(2).all(|pair| pair[0] >= pair[1]),
+        LoanError::InvalidConfigValue
+    );
+
+    ctx.accounts.program_state.risk_multipliers = risk_multipliers;
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::UpdateRiskMultipliers,
+        ctx.accounts.program_state.key(),
+        Clock::get()?.unix_timestamp,
+    );
+
+    msg!("Risk multipliers updated: {:?}", risk_multipliers);
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::RiskScoreUpdated;
+use crate::events::{emit_admin_action, RecommendationZeroed, RiskScoreUpdated};
 
 #[derive(Accounts)]
 pub struct UpdateRiskScore<'info> {
@@ -21,6 +21,13 @@ pub struct UpdateRiskScore<'info> {
     )]
     pub risk_profile: Account<'info, RiskProfile>,
 
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
     /// CHECK: User authority
     pub user: AccountInfo<'info>,
 
@@ -39,48 +46,85 @@ pub fn handler(
     require!(risk_score <= 1000, LoanError::InvalidRiskScore);
     require!(default_probability <= 10000, LoanError::InvalidDefaultProbability);
 
-    let user_profile = &mut ctx.accounts.user_profile;
-    let risk_profile = &mut ctx.accounts.risk_profile;
     let clock = Clock::get()?;
 
+    apply_risk_score(
+        &mut ctx.accounts.user_profile,
+        &mut ctx.accounts.risk_profile,
+        &ctx.accounts.program_state,
+        ctx.accounts.user.key(),
+        risk_score,
+        Some(risk_level),
+        default_probability,
+        ctx.bumps.risk_profile,
+        clock.unix_timestamp,
+    )?;
+    ctx.accounts.risk_profile.factors_count = 5; // Placeholder
+
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::UpdateRiskScore, ctx.accounts.user.key(), clock.unix_timestamp);
+
+    msg!("Risk score updated for user: score={}, level={:?}", risk_score, ctx.accounts.risk_profile.risk_level);
+
+    Ok(())
+}
+
+/// Write a score to the profile and risk profile, refresh the recommendation and emit
+/// RiskScoreUpdated; shared by the admin override and the on-chain computation.
+/// An assessed level can only add severity on top of what the score implies.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_risk_score(
+    user_profile: &mut UserProfile,
+    risk_profile: &mut RiskProfile,
+    program_state: &LoanProgramState,
+    user: Pubkey,
+    risk_score: u16,
+    assessed_level: Option<RiskLevel>,
+    default_probability: u16,
+    risk_profile_bump: u8,
+    now: i64,
+) -> Result<()> {
     let old_score = user_profile.credit_score;
 
     // Update user profile
     user_profile.credit_score = risk_score;
-    user_profile.risk_level = risk_level.clone();
-    user_profile.last_updated = clock.unix_timestamp;
+    user_profile.sync_risk_level(program_state);
+    if let Some(level) = assessed_level {
+        user_profile.risk_level = user_profile.risk_level.clone().max(level);
+    }
+    user_profile.last_updated = now;
 
     // Update risk profile
-    risk_profile.user = ctx.accounts.user.key();
+    risk_profile.user = user;
     risk_profile.risk_score = risk_score;
-    risk_profile.risk_level = risk_level.clone();
+    risk_profile.risk_level = user_profile.risk_level.clone();
     risk_profile.default_probability = default_probability;
     
     // Calculate recommended max loan based on income and risk
-    let income_multiplier = match risk_level {
-        RiskLevel::Low => 10,
-        RiskLevel::Medium => 6,
-        RiskLevel::High => 3,
-        RiskLevel::Critical => 1,
-    };
-    risk_profile.recommended_max_loan = user_profile.monthly_income
-        .checked_mul(income_multiplier)
-        .ok_or(LoanError::MathOverflow)?;
+    let min_income = program_state.min_income_for_recommendation;
+    if risk_profile.apply_recommendation(user_profile.monthly_income, program_state)? {
+        risk_profile.risk_level = risk_profile.risk_level.escalated();
+        user_profile.risk_level = risk_profile.risk_level.clone();
+
+        emit!(RecommendationZeroed {
+            user,
+            monthly_income: user_profile.monthly_income,
+            min_income_for_recommendation: min_income,
+            risk_level: risk_profile.risk_level.clone(),
+            timestamp: now,
+        });
+    }
     
-    risk_profile.last_calculated = clock.unix_timestamp;
-    risk_profile.factors_count = 5; // Placeholder
-    risk_profile.bump = ctx.bumps.risk_profile;
+    risk_profile.last_calculated = now;
+    risk_profile.bump = risk_profile_bump;
 
     emit!(RiskScoreUpdated {
-        user: ctx.accounts.user.key(),
+        user,
         old_score,
         new_score: risk_score,
-        risk_level,
+        risk_level: risk_profile.risk_level.clone(),
         default_probability,
-        timestamp: clock.unix_timestamp,
+        timestamp: now,
     });
 
-    msg!("Risk score updated for user: score={}, level={:?}", risk_score, risk_level);
-
     Ok(())
 }
@@ -1,17 +1,24 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
+use crate::events::{DtiBreach, IncomeChangeThrottled, UserProfileUpdated};
 
 #[derive(Accounts)]
 pub struct UpdateUserProfile<'info> {
     #[account(
         mut,
-        seeds = [b"user-profile", authority.key().as_ref()],
+        seeds = [b"user-profile", user_profile.authority.as_ref()],
         bump = user_profile.bump,
-        has_one = authority @ LoanError::Unauthorized
+        constraint = user_profile.is_authorized(authority.key()) @ LoanError::Unauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
 
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
     pub authority: Signer<'info>,
 }
 
@@ -21,11 +28,55 @@ pub fn handler(
     employment_type: Option<EmploymentType>,
 ) -> Result<()> {
     let user_profile = &mut ctx.accounts.user_profile;
+    let program_state = &ctx.accounts.program_state;
     let clock = Clock::get()?;
+    let old_income = user_profile.monthly_income;
+    let old_employment = user_profile.employment_type.clone();
 
     if let Some(income) = monthly_income {
         require!(income > 0, LoanError::IncomeTooLow);
+
+        // Rapid income edits look like underwriting being gamed
+        if clock.unix_timestamp - user_profile.income_change_window_start >= program_state.income_change_window_seconds {
+            user_profile.income_change_window_start = clock.unix_timestamp;
+            user_profile.income_change_count = 0;
+        }
+        user_profile.income_change_count = user_profile.income_change_count.saturating_add(1);
+
+        let max_changes = program_state.max_income_changes_per_window;
+        if max_changes > 0 && user_profile.income_change_count > max_changes {
+            require!(!program_state.reject_excess_income_changes, LoanError::TooManyIncomeChanges);
+
+            emit!(IncomeChangeThrottled {
+                user: user_profile.authority,
+                changes_in_window: user_profile.income_change_count,
+                max_changes_per_window: max_changes,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
         user_profile.monthly_income = income;
+
+        // Income drops are always accepted, but surface a DTI breach against active loans
+        if user_profile.active_loans > 0 {
+            if let Some(dti_bps) = user_profile.debt_to_income_bps() {
+                if dti_bps > program_state.max_dti_bps as u64 {
+                    if program_state.escalate_risk_on_dti_breach {
+                        user_profile.risk_level = user_profile.risk_level.escalated();
+                    }
+
+                    emit!(DtiBreach {
+                        user: user_profile.authority,
+                        monthly_income: income,
+                        monthly_obligations: user_profile.monthly_obligations,
+                        dti_bps,
+                        max_dti_bps: program_state.max_dti_bps,
+                        risk_level: user_profile.risk_level.clone(),
+                        timestamp: clock.unix_timestamp,
+                    });
+                }
+            }
+        }
     }
 
     if let Some(emp_type) = employment_type {
@@ -34,6 +85,15 @@ pub fn handler(
 
     user_profile.last_updated = clock.unix_timestamp;
 
+    emit!(UserProfileUpdated {
+        user: user_profile.authority,
+        old_income,
+        new_income: user_profile.monthly_income,
+        old_employment,
+        new_employment: user_profile.employment_type.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!("User profile updated for: {}", user_profile.full_name);
 
     Ok(())
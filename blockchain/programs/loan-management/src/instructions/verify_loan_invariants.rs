@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+
+#[derive(Accounts)]
+pub struct VerifyLoanInvariants<'info> {
+    #[account(
+        seeds = [b"loan", loan.user.as_ref(), &loan.loan_id.to_le_bytes()],
+        bump = loan.bump
+    )]
+    pub loan: Account<'info, Loan>,
+}
+
+pub fn handler(ctx: Context<VerifyLoanInvariants>) -> Result<()> {
+    let loan = &ctx.accounts.loan;
+
+    loan.validate_status_timestamps()?;
+    require!(loan.outstanding_balance <= loan.total_amount, LoanError::MathOverflow);
+    require!(loan.installments_paid <= loan.tenure_months, LoanError::InvalidInstallmentNumber);
+
+    msg!("Loan {} invariants hold", loan.loan_id);
+
+    Ok(())
+}
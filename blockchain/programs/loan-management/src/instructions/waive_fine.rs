@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LoanError;
-use crate::events::FineWaived;
+use crate::events::{emit_admin_action, FineWaived};
 
 #[derive(Accounts)]
 #[instruction(installment_number: u8)]
@@ -31,6 +31,13 @@ pub struct WaiveFine<'info> {
     )]
     pub payment_record: Account<'info, PaymentRecord>,
 
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        constraint = program_state.authority == admin.key() @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
     pub admin: Signer<'info>,
 }
 
@@ -40,14 +47,22 @@ pub fn handler(
     waived_amount: u64,
 ) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
-    let payment_record = &ctx.accounts.payment_record;
+    let payment_record = &mut ctx.accounts.payment_record;
     let clock = Clock::get()?;
 
-    require!(waived_amount <= payment_record.fine_amount, LoanError::InvalidPaymentAmount);
+    // Over-waives are rejected outright so a mistyped amount can't silently clamp
+    require!(
+        waived_amount > 0 && waived_amount <= payment_record.outstanding_fine(),
+        LoanError::InvalidPaymentAmount
+    );
+
+    payment_record.fine_waived = payment_record.fine_waived.checked_add(waived_amount)
+        .ok_or(LoanError::MathOverflow)?;
 
     // Update loan to reduce outstanding and fines
     loan.outstanding_balance = loan.outstanding_balance.saturating_sub(waived_amount);
-    loan.total_fines = loan.total_fines.saturating_sub(waived_amount);
+    loan.total_fines = loan.total_fines.checked_sub(waived_amount)
+        .ok_or(LoanError::InvalidPaymentAmount)?;
 
     emit!(FineWaived {
         loan: loan.key(),
@@ -58,6 +73,8 @@ pub fn handler(
         timestamp: clock.unix_timestamp,
     });
 
+    emit_admin_action(ctx.accounts.admin.key(), AdminActionCode::WaiveFine, loan.key(), clock.unix_timestamp);
+
     msg!("Fine waived: Loan={}, Installment={}, Amount={}", 
         loan.loan_id, installment_number, waived_amount);
 
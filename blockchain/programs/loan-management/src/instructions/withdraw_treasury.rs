@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LoanError;
+use crate::events::{emit_admin_action, TreasuryWithdrawn};
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        seeds = [b"program-state"],
+        bump = program_state.bump,
+        has_one = authority @ LoanError::Unauthorized
+    )]
+    pub program_state: Account<'info, LoanProgramState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    let clock = Clock::get()?;
+
+    // The rent-exempt minimum stays behind so the treasury account survives
+    let rent_floor = Rent::get()?.minimum_balance(Treasury::LEN);
+    let available = treasury.get_lamports().saturating_sub(rent_floor);
+    require!(amount > 0 && amount <= available, LoanError::InsufficientTreasuryFunds);
+
+    treasury.sub_lamports(amount)?;
+    ctx.accounts.authority.add_lamports(amount)?;
+    treasury.total_withdrawn = treasury.total_withdrawn.checked_add(amount)
+        .ok_or(LoanError::MathOverflow)?;
+
+    emit!(TreasuryWithdrawn {
+        amount,
+        recipient: ctx.accounts.authority.key(),
+        remaining: available - amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit_admin_action(
+        ctx.accounts.authority.key(),
+        AdminActionCode::WithdrawTreasury,
+        treasury.key(),
+        clock.unix_timestamp,
+    );
+
+    msg!("Treasury withdrawal: {} lamports to {}", amount, ctx.accounts.authority.key());
+
+    Ok(())
+}
@@ -45,6 +45,8 @@ pub mod loan_management {
         interest_rate: u16,
         tenure_months: u8,
         start_timestamp: i64,
+        with_insurance: bool,
+        spread_bps: Option<u16>,
     ) -> Result<()> {
         instructions::create_loan::handler(
             ctx,
@@ -52,6 +54,8 @@ pub mod loan_management {
             interest_rate,
             tenure_months,
             start_timestamp,
+            with_insurance,
+            spread_bps,
         )
     }
 
@@ -61,11 +65,18 @@ pub mod loan_management {
         installment_number: u8,
         amount: u64,
         payment_hash: String,
+        payment_ref_format: PaymentRefFormat,
     ) -> Result<()> {
-        instructions::record_payment::handler(ctx, installment_number, amount, payment_hash)
+        instructions::record_payment::handler(
+            ctx,
+            installment_number,
+            amount,
+            payment_hash,
+            payment_ref_format,
+        )
     }
 
-    /// Calculate and update risk score for a user
+    /// Admin override: set a risk score assessed off-chain (prefer compute_risk_score)
     pub fn update_risk_score(
         ctx: Context<UpdateRiskScore>,
         risk_score: u16,
@@ -98,4 +109,338 @@ pub mod loan_management {
     pub fn get_credit_score(ctx: Context<GetCreditScore>) -> Result<u16> {
         instructions::get_credit_score::handler(ctx)
     }
+
+    /// Update tunable program parameters (authority only)
+    pub fn update_program_config(
+        ctx: Context<UpdateProgramConfig>,
+        params: ProgramConfigParams,
+    ) -> Result<()> {
+        instructions::update_program_config::handler(ctx, params)
+    }
+
+    /// Attach a servicing note to a loan (admin only)
+    pub fn add_loan_note(ctx: Context<AddLoanNote>, text: String) -> Result<()> {
+        instructions::add_loan_note::handler(ctx, text)
+    }
+
+    /// Get the principal/interest split of a scheduled installment
+    pub fn get_amortization_split(
+        ctx: Context<GetAmortizationSplit>,
+        installment_number: u8,
+    ) -> Result<AmortizationSplit> {
+        instructions::get_amortization_split::handler(ctx, installment_number)
+    }
+
+    /// Get due dates and principal/interest splits for a page of the repayment schedule
+    pub fn get_amortization_schedule(
+        ctx: Context<GetAmortizationSchedule>,
+        start_installment: u8,
+        count: u8,
+    ) -> Result<Vec<InstallmentDue>> {
+        instructions::get_amortization_schedule::handler(ctx, start_installment, count)
+    }
+
+    /// Project the payoff date and interest saved at a hypothetical monthly payment
+    pub fn project_payoff(ctx: Context<ProjectPayoff>, monthly_payment: u64) -> Result<PayoffProjection> {
+        instructions::project_payoff::handler(ctx, monthly_payment)
+    }
+
+    /// Check a loan's internal consistency without modifying it
+    pub fn verify_loan_invariants(ctx: Context<VerifyLoanInvariants>) -> Result<()> {
+        instructions::verify_loan_invariants::handler(ctx)
+    }
+
+    /// Recompute recommended max loan from current income without rescoring
+    pub fn refresh_loan_recommendation(ctx: Context<RefreshLoanRecommendation>) -> Result<()> {
+        instructions::refresh_loan_recommendation::handler(ctx)
+    }
+
+    /// Get the complete picture of a loan and its borrower in one call
+    pub fn get_loan_full(ctx: Context<GetLoanFull>) -> Result<LoanFullView> {
+        instructions::get_loan_full::handler(ctx)
+    }
+
+    /// Record funds recovered on a defaulted loan (admin only)
+    pub fn record_recovery(
+        ctx: Context<RecordRecovery>,
+        recovered_amount: u64,
+        restore_score: bool,
+    ) -> Result<()> {
+        instructions::record_recovery::handler(ctx, recovered_amount, restore_score)
+    }
+
+    /// Get a borrower's debt-to-income ratio alongside the configured limit
+    pub fn get_dti(ctx: Context<GetDti>) -> Result<DtiView> {
+        instructions::get_dti::handler(ctx)
+    }
+
+    /// Correct program counters to values reconciled off-chain (authority only)
+    pub fn adjust_program_counters(
+        ctx: Context<AdjustProgramCounters>,
+        adjustment: CounterAdjustment,
+        reason: String,
+    ) -> Result<()> {
+        instructions::adjust_program_counters::handler(ctx, adjustment, reason)
+    }
+
+    /// Register a smart-wallet (PDA) borrower whose rent is paid by a separate payer
+    pub fn register_wallet_user(
+        ctx: Context<RegisterWalletUser>,
+        full_name: String,
+        monthly_income: u64,
+        employment_type: EmploymentType,
+        wallet_seeds: Vec<Vec<u8>>,
+        wallet_bump: u8,
+    ) -> Result<()> {
+        instructions::register_wallet_user::handler(
+            ctx,
+            full_name,
+            monthly_income,
+            employment_type,
+            wallet_seeds,
+            wallet_bump,
+        )
+    }
+
+    /// Get every tunable program parameter in one call
+    pub fn get_program_config(ctx: Context<GetProgramConfig>) -> Result<ProgramConfigView> {
+        instructions::get_program_config::handler(ctx)
+    }
+
+    /// Cover a defaulted insured loan's outstanding balance from the insurance pool (admin only)
+    pub fn claim_insurance(ctx: Context<ClaimInsurance>) -> Result<()> {
+        instructions::claim_insurance::handler(ctx)
+    }
+
+    /// Crank: emit the next payment reminder stage due for a loan's upcoming installment
+    pub fn emit_reminder_ladder(ctx: Context<EmitReminderLadder>) -> Result<()> {
+        instructions::emit_reminder_ladder::handler(ctx)
+    }
+
+    /// Create the rate oracle and name its feeder (admin only)
+    pub fn init_rate_oracle(ctx: Context<InitRateOracle>, feeder: Pubkey, initial_rate_bps: u16) -> Result<()> {
+        instructions::init_rate_oracle::handler(ctx, feeder, initial_rate_bps)
+    }
+
+    /// Publish a new reference rate (feeder only)
+    pub fn update_oracle_rate(ctx: Context<UpdateOracleRate>, rate_bps: u16) -> Result<()> {
+        instructions::update_oracle_rate::handler(ctx, rate_bps)
+    }
+
+    /// Apply the one-time financial-education score boost (admin only)
+    pub fn record_financial_education(ctx: Context<RecordFinancialEducation>) -> Result<()> {
+        instructions::record_financial_education::handler(ctx)
+    }
+
+    /// Crank: default a loan still carrying a balance past maturity plus grace, or one
+    /// with missed_installments_to_default installments marked missed
+    pub fn auto_default_matured(ctx: Context<AutoDefaultMatured>) -> Result<()> {
+        instructions::auto_default_matured::handler(ctx)
+    }
+
+    /// Get whether an installment is before due, within grace, or overdue right now
+    pub fn get_installment_grace_status(
+        ctx: Context<GetInstallmentGraceStatus>,
+        installment_number: u8,
+    ) -> Result<InstallmentGraceStatus> {
+        instructions::get_installment_grace_status::handler(ctx, installment_number)
+    }
+
+    /// Set the borrower's off-chain contact commitment and notification opt-in
+    pub fn update_notification_preferences(
+        ctx: Context<UpdateNotificationPreferences>,
+        contact_hash: [u8; 32],
+        notification_opt_in: bool,
+    ) -> Result<()> {
+        instructions::update_notification_preferences::handler(ctx, contact_hash, notification_opt_in)
+    }
+
+    /// Set the income multiplier applied at each risk level
+    pub fn update_risk_multipliers(
+        ctx: Context<UpdateRiskMultipliers>,
+        risk_multipliers: [u16; 4],
+    ) -> Result<()> {
+        instructions::update_risk_multipliers::handler(ctx, risk_multipliers)
+    }
+
+    /// Crank: flag a loan whose oldest unpaid installment is past npl_days as non-performing
+    pub fn mark_non_performing(ctx: Context<MarkNonPerforming>) -> Result<()> {
+        instructions::mark_non_performing::handler(ctx)
+    }
+
+    /// Get the fine and total due if an installment were paid right now
+    pub fn get_current_fine(
+        ctx: Context<GetCurrentFine>,
+        installment_number: u8,
+    ) -> Result<CurrentFineView> {
+        instructions::get_current_fine::handler(ctx, installment_number)
+    }
+
+    /// Void an active loan that has never been serviced
+    pub fn cancel_loan(ctx: Context<CancelLoan>) -> Result<()> {
+        instructions::cancel_loan::handler(ctx)
+    }
+
+    /// Pause or resume registration and origination
+    pub fn set_pause_state(ctx: Context<SetPauseState>, paused: bool) -> Result<()> {
+        instructions::set_pause_state::handler(ctx, paused)
+    }
+
+    /// Propose a new program authority; takes effect once they accept
+    pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+        instructions::transfer_authority::handler(ctx, new_authority)
+    }
+
+    /// Accept a pending authority transfer as the proposed successor
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority::handler(ctx)
+    }
+
+    /// Count an unpaid installment past its grace period as missed
+    pub fn mark_installment_missed(ctx: Context<MarkInstallmentMissed>, installment_number: u8) -> Result<()> {
+        instructions::mark_installment_missed::handler(ctx, installment_number)
+    }
+
+    /// Add or remove the second authority on a joint profile
+    pub fn set_co_authority(ctx: Context<SetCoAuthority>, co_authority: Option<Pubkey>) -> Result<()> {
+        instructions::set_co_authority::handler(ctx, co_authority)
+    }
+
+    /// Pay part of an installment; it completes once the parts cover the amount due
+    pub fn record_partial_payment(ctx: Context<RecordPartialPayment>, installment_number: u8, amount: u64) -> Result<()> {
+        instructions::record_partial_payment::handler(ctx, installment_number, amount)
+    }
+
+    /// Close a completed or cancelled loan and its records once retention has passed
+    pub fn auto_close_terminal<'info>(ctx: Context<'_, '_, 'info, 'info, AutoCloseTerminal<'info>>) -> Result<()> {
+        instructions::auto_close_terminal::handler(ctx)
+    }
+
+    /// Clear the whole remaining balance early, less any unearned-interest rebate
+    pub fn prepay_loan(ctx: Context<PrepayLoan>, amount: u64) -> Result<()> {
+        instructions::prepay_loan::handler(ctx, amount)
+    }
+
+    /// Set the registration cap for a controlled rollout (0 = unlimited)
+    pub fn set_max_users(ctx: Context<SetMaxUsers>, max_users: u64) -> Result<()> {
+        instructions::set_max_users::handler(ctx, max_users)
+    }
+
+    /// Set how many loans a single borrower may hold open at once
+    pub fn set_max_active_loans(ctx: Context<SetMaxActiveLoans>, max_active_loans: u8) -> Result<()> {
+        instructions::set_max_active_loans::handler(ctx, max_active_loans)
+    }
+
+    /// Change the late-fee grace period and daily fine rate
+    pub fn update_fine_params(
+        ctx: Context<UpdateFineParams>,
+        grace_period_days: Option<u8>,
+        daily_fine_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::update_fine_params::handler(ctx, grace_period_days, daily_fine_bps)
+    }
+
+    /// Grow a program state created before the fine parameters existed and fill in their defaults
+    pub fn migrate_program_state(ctx: Context<MigrateProgramState>) -> Result<()> {
+        instructions::migrate_program_state::handler(ctx)
+    }
+
+    /// Set which employment types may register but not borrow
+    pub fn set_employment_blocklist(ctx: Context<SetEmploymentBlocklist>, blocked_mask: u8) -> Result<()> {
+        instructions::set_employment_blocklist::handler(ctx, blocked_mask)
+    }
+
+    /// Get a loan's balance, progress and next due date in one small struct
+    pub fn get_loan_summary(ctx: Context<GetLoanSummary>) -> Result<LoanSummary> {
+        instructions::get_loan_summary::handler(ctx)
+    }
+
+    /// Get a bitmask of the factors a borrower can act on to improve their credit score
+    pub fn get_score_improvement_tips(ctx: Context<GetScoreImprovementTips>) -> Result<u8> {
+        instructions::get_score_improvement_tips::handler(ctx)
+    }
+
+    /// Lock collateral against an active loan, making it a secured loan
+    pub fn lock_collateral(
+        ctx: Context<LockCollateral>,
+        collateral_type: CollateralType,
+        declared_value: u64,
+    ) -> Result<()> {
+        instructions::lock_collateral::handler(ctx, collateral_type, declared_value)
+    }
+
+    /// Release a completed loan's collateral back to the borrower
+    pub fn release_collateral(ctx: Context<ReleaseCollateral>) -> Result<()> {
+        instructions::release_collateral::handler(ctx)
+    }
+
+    /// Co-sign an active loan as its guarantor
+    pub fn add_guarantor(ctx: Context<AddGuarantor>, guaranteed_amount: u64) -> Result<()> {
+        instructions::add_guarantor::handler(ctx, guaranteed_amount)
+    }
+
+    /// Derive the risk score from the borrower's own profile; preferred over update_risk_score
+    pub fn compute_risk_score(ctx: Context<ComputeRiskScore>) -> Result<()> {
+        instructions::compute_risk_score::handler(ctx)
+    }
+
+    /// Re-amortize a struggling borrower's outstanding balance over a new tenure and/or rate
+    pub fn restructure_loan(
+        ctx: Context<RestructureLoan>,
+        new_tenure_months: Option<u8>,
+        new_interest_rate: Option<u16>,
+        fine_handling: RestructureFineHandling,
+    ) -> Result<()> {
+        instructions::restructure_loan::handler(ctx, new_tenure_months, new_interest_rate, fine_handling)
+    }
+
+    /// Close a completed or defaulted loan and its payment records, returning the rent
+    pub fn close_loan<'info>(ctx: Context<'_, '_, 'info, 'info, CloseLoan<'info>>) -> Result<()> {
+        instructions::close_loan::handler(ctx)
+    }
+
+    /// Create the treasury that receives fines settled on-chain
+    pub fn init_treasury(ctx: Context<InitTreasury>) -> Result<()> {
+        instructions::init_treasury::handler(ctx)
+    }
+
+    /// Move accumulated treasury funds to the authority
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        instructions::withdraw_treasury::handler(ctx, amount)
+    }
+
+    /// Undo the latest installment payment entered in error, reopening a completed loan if asked
+    pub fn reverse_payment(ctx: Context<ReversePayment>, installment_number: u8, reopen: bool) -> Result<()> {
+        instructions::reverse_payment::handler(ctx, installment_number, reopen)
+    }
+
+    /// Re-count a loan in the risk-level stats under its borrower's current level (permissionless)
+    pub fn refresh_loan_segment(ctx: Context<RefreshLoanSegment>) -> Result<()> {
+        instructions::refresh_loan_segment::handler(ctx)
+    }
+
+    /// Get active loans, outstanding balance and defaults broken down by risk level
+    pub fn get_stats_by_risk_level(ctx: Context<GetStatsByRiskLevel>) -> Result<RiskLevelStats> {
+        instructions::get_stats_by_risk_level::handler(ctx)
+    }
+
+    /// Approve a pre-priced credit line for a repeat borrower in good standing (admin only)
+    pub fn open_credit_line(ctx: Context<OpenCreditLine>, limit: u64, interest_rate: u16) -> Result<()> {
+        instructions::open_credit_line::handler(ctx, limit, interest_rate)
+    }
+
+    /// Take a loan against an open credit line without full re-underwriting
+    pub fn draw_from_credit_line(ctx: Context<DrawFromCreditLine>, amount: u64, tenure_months: u8) -> Result<()> {
+        instructions::draw_from_credit_line::handler(ctx, amount, tenure_months)
+    }
+
+    /// Move an unserviced loan onto stepped installments weighted by per-installment multipliers (admin only)
+    pub fn create_step_schedule(ctx: Context<CreateStepSchedule>, multipliers_bps: Vec<u16>) -> Result<()> {
+        instructions::create_step_schedule::handler(ctx, multipliers_bps)
+    }
+
+    /// Get the principal, fines and defaulted balance at risk across the whole portfolio
+    pub fn get_portfolio_exposure(ctx: Context<GetPortfolioExposure>) -> Result<PortfolioExposure> {
+        instructions::get_portfolio_exposure::handler(ctx)
+    }
 }
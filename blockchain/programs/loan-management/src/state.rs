@@ -1,4 +1,37 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::errors::LoanError;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// Installments fall due every 30 days from the loan start
+pub const SECONDS_PER_PERIOD: i64 = 30 * SECONDS_PER_DAY;
+pub const MAX_FINE_BPS: u64 = 1000; // the daily fine rate never exceeds 10% of the installment
+
+/// Whole days an installment is past its grace window (0 while still inside it)
+pub fn days_late_after_grace(due_timestamp: i64, now: i64, grace_period_seconds: i64) -> u16 {
+    let seconds_past_grace = (now - due_timestamp - grace_period_seconds).max(0);
+    (seconds_past_grace / SECONDS_PER_DAY).min(u16::MAX as i64) as u16
+}
+
+/// Canonical late fine: daily_fine_bps of the installment per day late, capped at
+/// cap_bps of it. The cap never drops below min_cap (itself bounded by the
+/// installment), since on tiny installments the basis-point cap rounds down to
+/// zero. SINGLE_FILE_PROGRAM.rs carries an identical copy; keep them in step.
+pub fn compute_fine(monthly_installment: u64, days_late: u16, daily_fine_bps: u16, cap_bps: u16, min_cap: u64) -> u64 {
+    let accrued = (monthly_installment as u128) * (daily_fine_bps as u128) * (days_late as u128) / 10000;
+    let cap = ((monthly_installment as u128) * (cap_bps as u128) / 10000)
+        .max(min_cap.min(monthly_installment) as u128);
+    accrued.min(cap) as u64
+}
+
+/// Default probability in basis points implied by a computed score: 50% at the
+/// bottom of the score range falling linearly to 1% at the top
+pub fn default_probability_for(score: u16) -> u16 {
+    let min = LoanProgramState::MIN_CREDIT_SCORE as u32;
+    let max = LoanProgramState::MAX_CREDIT_SCORE as u32;
+    let above_min = (score as u32).clamp(min, max) - min;
+    (5000 - above_min * 4900 / (max - min)) as u16
+}
 
 /// Main program state account
 #[account]
@@ -7,13 +40,367 @@ pub struct LoanProgramState {
     pub total_users: u64,
     pub total_loans: u64,
     pub total_volume: u64,
+    /// Origination fee in basis points of principal, withheld from the disbursement
     pub fee_percentage: u16,
     pub paused: bool,
+    /// Debt-to-income limit in basis points of monthly income
+    pub max_dti_bps: u16,
+    /// Escalate a borrower's risk level when an income update breaches the DTI limit
+    pub escalate_risk_on_dti_breach: bool,
+    /// Allow installments that don't cover the first period's interest
+    pub allow_neg_am: bool,
+    /// Circuit breaker: auto-pause when origination in a window exceeds the thresholds
+    pub anomaly_detection_enabled: bool,
+    pub anomaly_window_seconds: i64,
+    /// Max loans per window (0 = no count limit)
+    pub anomaly_max_loans: u32,
+    /// Max principal volume per window (0 = no volume limit)
+    pub anomaly_max_volume: u64,
+    pub anomaly_window_start: i64,
+    pub anomaly_window_loans: u32,
+    pub anomaly_window_volume: u64,
+    /// Fine discount for borrowers on their first loan, in basis points of the fine
+    pub first_loan_fine_discount_bps: u16,
+    /// Minimum seconds between consecutive payments on a loan (0 = no limit)
+    pub min_payment_gap_seconds: i64,
+    /// Allowed interest-rate range per credit-score band, ascending by min_score
+    pub rate_bands: [RateBand; 4],
+    /// Amount collected on defaulted loans after write-off
+    pub total_recovered: u64,
+    /// Oldest a borrower's profile may be (since last update) to originate a loan
+    pub max_profile_age_for_loan: i64,
+    /// Credit score gained on completing a loan
+    pub completion_bonus: u16,
+    /// Credit score lost on defaulting a loan
+    pub default_penalty: u16,
+    /// Owner program of smart-wallet borrower PDAs (default key = wallet borrowers disabled)
+    pub borrower_wallet_program: Pubkey,
+    /// Credit-life premium collected upfront on insured loans, in basis points of principal
+    pub insurance_premium_bps: u16,
+    /// Premiums collected less claims paid out
+    pub insurance_pool: u64,
+    /// Borrowers scoring above this get score_discount_bps off the offered rate
+    pub discount_score_threshold: u16,
+    pub score_discount_bps: u16,
+    /// Lowest rate the score discount can bring a loan down to
+    pub discount_rate_floor: u16,
+    /// Reminder stages as seconds relative to the due date (negative = before), ascending
+    pub reminder_ladder_offsets: [i64; 5],
+    /// Rate oracle driving floating-rate loans (default key = fixed-rate only)
+    pub rate_oracle: Pubkey,
+    /// Margin added on top of the oracle rate
+    pub oracle_spread_bps: u16,
+    /// One-time credit score boost for completing financial education
+    pub education_score_boost: u16,
+    /// Days past end_timestamp before an unpaid loan can be auto-defaulted
+    pub grace_after_maturity_days: u16,
+    /// Cap on schedule plus fines as basis points of principal (0 = uncapped)
+    pub max_total_repayment_multiplier_bps: u16,
+    /// Income below this zeroes the recommended max loan and marks the borrower ineligible
+    pub min_income_for_recommendation: u64,
+    /// Bounds on the spread a floating-rate loan may carry over the oracle
+    pub min_spread_bps: u16,
+    pub max_spread_bps: u16,
+    /// Minimum credit score for Low, Medium and High risk; anything below is Critical
+    pub risk_level_cutoffs: [u16; 3],
+    /// Smallest regular installment worth servicing (0 = no minimum)
+    pub min_installment_amount: u64,
+    /// Months of income recommended at Low, Medium, High and Critical risk
+    pub risk_multipliers: [u16; 4],
+    /// Days the oldest unpaid installment may be overdue before the loan is non-performing
+    pub npl_days: u16,
+    /// Credit score penalty on entering non-performing; smaller than default_penalty
+    pub npl_penalty: u16,
+    /// Residual balance a loan may still carry when completed; it's written off as rounding dust
+    pub completion_dust_tolerance: u64,
+    /// Income updates a borrower may make per window (0 = unlimited)
+    pub max_income_changes_per_window: u8,
+    pub income_change_window_seconds: i64,
+    /// Reject changes past the limit; when false they go through but are flagged
+    pub reject_excess_income_changes: bool,
+    /// Proposed successor authority; must accept before taking over (default key = none)
+    pub pending_authority: Pubkey,
+    /// Anti-fraud seasoning: seconds a profile must exist before its first approval (0 = none)
+    pub min_account_age_for_loan: i64,
+    /// Credit score penalty for each installment marked missed; heavier than a late payment's
+    pub missed_payment_penalty: u16,
+    /// Seconds a completed or cancelled loan is kept before anyone may close it
+    pub retention_period_seconds: i64,
+    /// Share of unearned interest rebated when a loan is prepaid in full (0 = none)
+    pub prepayment_rebate_bps: u16,
+    /// Registration cap for controlled rollouts (0 = unlimited)
+    pub max_users: u64,
+    /// Days after an installment falls due before it counts as late
+    pub grace_period_days: u8,
+    /// Fine per day late, in basis points of the installment
+    pub daily_fine_bps: u16,
+    /// Grace for the final installment only (0 = same as grace_period_days)
+    pub final_installment_grace_days: u8,
+    /// Bitmask of EmploymentType::bit values that may register but not borrow
+    pub blocked_employment_types: u8,
+    /// Round installments down to min_installment_amount multiples, the lender absorbing the shortfall
+    pub borrower_friendly_rounding: bool,
+    /// Origination throttle: loans create_loan may open in one slot (0 = unlimited)
+    pub max_loans_per_slot: u16,
+    /// Slot the throttle counter below belongs to
+    pub origination_slot: u64,
+    pub loans_in_origination_slot: u16,
+    /// Credit score penalty for a guarantor when the loan they back defaults (0 = none)
+    pub guarantor_default_penalty: u16,
+    /// Accept a part payment that exactly clears the fine without applying it to the installment
+    pub allow_fine_only_payments: bool,
+    /// Cap on routine credit score movement in each direction per window (0 = uncapped)
+    pub max_score_change_per_window: u16,
+    /// Length of the window max_score_change_per_window applies to
+    pub score_change_window_seconds: i64,
+    /// Underwriting gate: create_loan needs the borrower to have a RiskProfile
+    pub require_risk_profile: bool,
+    /// Times a single loan may be restructured (0 = restructuring off)
+    pub max_restructures_per_loan: u8,
+    /// Most one installment's fine can reach, in basis points of the installment
+    pub fine_cap_bps: u16,
+    /// Floor under the fine cap so tiny installments still accrue a fine (0 = no floor)
+    pub min_fine_cap: u64,
+    /// Score bonus for settling an installment before its due date (0 = none)
+    pub early_payment_bonus: u16,
+    /// Loans a single borrower may hold open at once
+    pub max_active_loans: u8,
+    /// Origination fees withheld from disbursements across all loans
+    pub total_fees_collected: u64,
+    /// Borrowers whose risk profile puts default odds above this (bps) are refused loans (0 = off)
+    pub max_default_probability_for_loan: u16,
+    /// Move each fine and servicing fee from the payer to the treasury in lamports as it is paid
+    pub settle_fines_on_chain: bool,
+    /// Flat servicing fee added to every installment of new loans; a charge, not a penalty (0 = none)
+    pub servicing_fee_per_installment: u64,
+    /// Active loans and their outstanding balance by the borrower's risk level
+    /// (indexed Low..Critical), plus the loans that defaulted from each level
+    pub active_loans_by_level: [u32; 4],
+    pub outstanding_by_level: [u64; 4],
+    pub defaults_by_level: [u32; 4],
+    /// Completed loans a borrower needs before a credit line can be opened for them
+    pub credit_line_min_completed_loans: u8,
+    /// A default this soon after the borrower's last completion takes back that completion's bonus (0 disables)
+    pub bonus_clawback_window_seconds: i64,
+    /// How far before origination a loan's start_timestamp may be set
+    pub max_start_backdate_seconds: i64,
+    /// How far after origination a loan's start_timestamp may be set
+    pub max_start_lead_seconds: i64,
+    /// Share of the installments a borrower guarantees for others counted toward their own DTI at origination
+    pub guarantee_dti_weight_bps: u16,
+    /// How many active loans one user may guarantee at a time
+    pub max_guarantees: u8,
+    /// Days after origination within which cancelling a loan refunds its origination fee
+    pub fee_refund_window_days: u16,
+    /// How long origination stays blocked after the program resumes, so a backlog isn't rushed through (0 = none)
+    pub post_unpause_cooldown_seconds: i64,
+    /// When set_pause_state last resumed the program (0 = never paused)
+    pub unpaused_at: i64,
+    /// Portfolio totals over active loans, kept in step with the risk-level stats:
+    /// principal still owed, fines assessed on part-paid installments or carried
+    /// past a restructure, and the balance of loans marked non-performing
+    pub outstanding_principal: u64,
+    pub outstanding_fines: u64,
+    pub at_risk_outstanding: u64,
+    /// Balance of defaulted loans not yet recovered, covered by insurance or closed
+    pub defaulted_outstanding: u64,
+    /// Order a part payment settles the fine and the installment's interest and principal in
+    pub payment_waterfall: PaymentWaterfall,
+    /// Installments marked missed on one loan that let auto_default_matured default it before maturity (0 = off)
+    pub missed_installments_to_default: u8,
     pub bump: u8,
 }
 
 impl LoanProgramState {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 2 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 2 + 1 + 2 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 4 + 8 + 2 + 8 + (RateBand::LEN * 4) + 8 + 8 + 2 + 2 + 32 + 2 + 8 + 2 + 2 + 2 + (8 * 5) + 32 + 2 + 2 + 2 + 2 + 8 + 2 + 2 + (2 * 3) + 8 + (2 * 4) + 2 + 2 + 8 + 1 + 8 + 1 + 32 + 8 + 2 + 8 + 2 + 8 + 1 + 2 + 1 + 1 + 1 + 2 + 8 + 2 + 2 + 1 + 2 + 8 + 1 + 1 + 2 + 8 + 2 + 1 + 8 + 2 + 1 + 8 + (4 * 4) + (8 * 4) + (4 * 4) + 1 + 8 + 8 + 8 + 2 + 1 + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1;
+    /// Account size before grace_period_days and daily_fine_bps existed; migrate_program_state upgrades from it
+    pub const PRE_FINE_PARAMS_LEN: usize = 411;
+
+    pub const DEFAULT_MAX_DTI_BPS: u16 = 5000; // 50%
+    pub const MAX_INTEREST_RATE: u16 = 3000; // 30%
+    pub const DEFAULT_MAX_PROFILE_AGE_FOR_LOAN: i64 = 365 * SECONDS_PER_DAY;
+    /// One default outweighs five completions by default
+    pub const DEFAULT_COMPLETION_BONUS: u16 = 20;
+    pub const DEFAULT_DEFAULT_PENALTY: u16 = 100;
+    /// 3 days before, on the due date, then 3/7/15 days overdue
+    pub const DEFAULT_REMINDER_LADDER_OFFSETS: [i64; 5] = [
+        -3 * SECONDS_PER_DAY,
+        0,
+        3 * SECONDS_PER_DAY,
+        7 * SECONDS_PER_DAY,
+        15 * SECONDS_PER_DAY,
+    ];
+    pub const DEFAULT_EDUCATION_SCORE_BOOST: u16 = 10;
+    pub const DEFAULT_GRACE_AFTER_MATURITY_DAYS: u16 = 30;
+    pub const DEFAULT_NPL_DAYS: u16 = 90;
+    pub const DEFAULT_NPL_PENALTY: u16 = 30;
+    pub const DEFAULT_MISSED_PAYMENT_PENALTY: u16 = 15;
+    /// Dust is rounding error, not a way to forgive real balances
+    pub const MAX_COMPLETION_DUST_TOLERANCE: u64 = 1_000;
+    pub const DEFAULT_MAX_INCOME_CHANGES_PER_WINDOW: u8 = 3;
+    pub const DEFAULT_INCOME_CHANGE_WINDOW_SECONDS: i64 = 30 * SECONDS_PER_DAY;
+    pub const DEFAULT_MIN_INCOME_FOR_RECOMMENDATION: u64 = 1_000_000_000_000; // 1k PKR
+    pub const DEFAULT_RETENTION_PERIOD_SECONDS: i64 = 730 * SECONDS_PER_DAY;
+    pub const DEFAULT_GRACE_PERIOD_DAYS: u8 = 2;
+    pub const DEFAULT_DAILY_FINE_BPS: u16 = 50; // 0.5% of the installment per day
+    pub const DEFAULT_GUARANTOR_DEFAULT_PENALTY: u16 = 30;
+    pub const DEFAULT_SCORE_CHANGE_WINDOW_SECONDS: i64 = 30 * SECONDS_PER_DAY;
+    pub const DEFAULT_MAX_RESTRUCTURES_PER_LOAN: u8 = 2;
+    pub const DEFAULT_FINE_CAP_BPS: u16 = 1000; // one installment's fine never exceeds 10% of it
+    pub const DEFAULT_CREDIT_LINE_MIN_COMPLETED_LOANS: u8 = 3;
+    pub const DEFAULT_BONUS_CLAWBACK_WINDOW_SECONDS: i64 = 90 * SECONDS_PER_DAY;
+    pub const DEFAULT_MAX_START_BACKDATE_SECONDS: i64 = 30 * SECONDS_PER_DAY;
+    pub const DEFAULT_MAX_START_LEAD_SECONDS: i64 = 365 * SECONDS_PER_DAY;
+    pub const DEFAULT_GUARANTEE_DTI_WEIGHT_BPS: u16 = 5000; // half of each guaranteed installment
+    pub const DEFAULT_MAX_GUARANTEES: u8 = 3;
+    pub const DEFAULT_FEE_REFUND_WINDOW_DAYS: u16 = 7;
+    pub const DEFAULT_PAYMENT_WATERFALL: PaymentWaterfall = PaymentWaterfall::InterestFirst;
+    pub const DEFAULT_RISK_LEVEL_CUTOFFS: [u16; 3] = [700, 500, 400];
+    pub const DEFAULT_RISK_MULTIPLIERS: [u16; 4] = [10, 6, 3, 1];
+    pub const MAX_RISK_MULTIPLIER: u16 = 60;
+    pub const MIN_CREDIT_SCORE: u16 = 300;
+    pub const MAX_CREDIT_SCORE: u16 = 850;
+    /// Score moves for each installment paid; missed_payment_penalty must exceed the late one
+    pub const ON_TIME_PAYMENT_REWARD: u16 = 2;
+    pub const LATE_PAYMENT_PENALTY: u16 = 5;
+
+    /// Bands start permissive so pricing is unconstrained until configured
+    pub const DEFAULT_RATE_BANDS: [RateBand; 4] = [
+        RateBand { min_score: 0, min_rate: 1, max_rate: Self::MAX_INTEREST_RATE },
+        RateBand { min_score: 600, min_rate: 1, max_rate: Self::MAX_INTEREST_RATE },
+        RateBand { min_score: 700, min_rate: 1, max_rate: Self::MAX_INTEREST_RATE },
+        RateBand { min_score: 800, min_rate: 1, max_rate: Self::MAX_INTEREST_RATE },
+    ];
+
+    /// Rebuild a pre-fine-params account image in the current layout. The old
+    /// layout ends `.., max_users, bump`; the new fields sit where the old bump
+    /// byte was, so the bump moves to the end. Fields added since come out zeroed,
+    /// and any whose default isn't zero must be filled in here or its feature is
+    /// silently off on migrated deployments.
+    pub fn migrate_pre_fine_params(old: &[u8]) -> Result<Self> {
+        require!(old.len() == Self::PRE_FINE_PARAMS_LEN, LoanError::NothingToMigrate);
+        require!(old[..8] == Self::DISCRIMINATOR, LoanError::NothingToMigrate);
+
+        let (fields, bump) = old.split_at(old.len() - 1);
+        let mut data = vec![0u8; Self::LEN];
+        data[..fields.len()].copy_from_slice(fields);
+        data[Self::LEN - 1] = bump[0];
+
+        let mut state = Self::try_deserialize(&mut &data[..])?;
+        state.grace_period_days = Self::DEFAULT_GRACE_PERIOD_DAYS;
+        state.daily_fine_bps = Self::DEFAULT_DAILY_FINE_BPS;
+        state.max_active_loans = 1;
+        state.fine_cap_bps = Self::DEFAULT_FINE_CAP_BPS;
+        state.max_start_backdate_seconds = Self::DEFAULT_MAX_START_BACKDATE_SECONDS;
+        state.max_start_lead_seconds = Self::DEFAULT_MAX_START_LEAD_SECONDS;
+        state.guarantor_default_penalty = Self::DEFAULT_GUARANTOR_DEFAULT_PENALTY;
+        state.score_change_window_seconds = Self::DEFAULT_SCORE_CHANGE_WINDOW_SECONDS;
+        state.max_restructures_per_loan = Self::DEFAULT_MAX_RESTRUCTURES_PER_LOAN;
+        state.credit_line_min_completed_loans = Self::DEFAULT_CREDIT_LINE_MIN_COMPLETED_LOANS;
+        state.bonus_clawback_window_seconds = Self::DEFAULT_BONUS_CLAWBACK_WINDOW_SECONDS;
+        state.guarantee_dti_weight_bps = Self::DEFAULT_GUARANTEE_DTI_WEIGHT_BPS;
+        state.max_guarantees = Self::DEFAULT_MAX_GUARANTEES;
+        state.fee_refund_window_days = Self::DEFAULT_FEE_REFUND_WINDOW_DAYS;
+        state.payment_waterfall = Self::DEFAULT_PAYMENT_WATERFALL;
+        Ok(state)
+    }
+
+    /// End of the origination cooldown that follows the last resume
+    pub fn post_unpause_cooldown_ends_at(&self) -> i64 {
+        self.unpaused_at.saturating_add(self.post_unpause_cooldown_seconds)
+    }
+
+    /// The highest band whose min_score the credit score reaches
+    pub fn rate_band_for(&self, credit_score: u16) -> RateBand {
+        self.rate_bands
+            .iter()
+            .rev()
+            .find(|band| credit_score >= band.min_score)
+            .copied()
+            .unwrap_or(self.rate_bands[0])
+    }
+
+    /// Months of income a borrower at this level is recommended to borrow
+    pub fn income_multiplier(&self, risk_level: &RiskLevel) -> u64 {
+        self.risk_multipliers[risk_level.index()] as u64
+    }
+
+    /// Re-count a loan in the risk-level stats: its previous contribution comes
+    /// out, and an active loan goes back in under the borrower's current level
+    /// at its current balance. A loan leaving a segment as Defaulted counts as
+    /// a default there. Call after any change to the loan's status or balance.
+    pub fn sync_loan_segment(&mut self, loan: &mut Loan, risk_level: &RiskLevel) {
+        if let Some(previous) = loan.risk_segment.take() {
+            let index = previous.index();
+            self.active_loans_by_level[index] = self.active_loans_by_level[index].saturating_sub(1);
+            self.outstanding_by_level[index] = self.outstanding_by_level[index].saturating_sub(loan.segment_balance);
+            self.outstanding_principal = self.outstanding_principal.saturating_sub(loan.segment_principal);
+            self.outstanding_fines = self.outstanding_fines.saturating_sub(loan.segment_fines);
+            if loan.segment_at_risk {
+                self.at_risk_outstanding = self.at_risk_outstanding.saturating_sub(loan.segment_balance);
+            }
+            if loan.status == LoanStatus::Defaulted {
+                self.defaults_by_level[index] = self.defaults_by_level[index].saturating_add(1);
+                self.defaulted_outstanding = self.defaulted_outstanding.saturating_add(loan.segment_balance);
+            }
+        }
+
+        loan.segment_balance = 0;
+        loan.segment_principal = 0;
+        loan.segment_fines = 0;
+        loan.segment_at_risk = false;
+        if loan.status == LoanStatus::Active {
+            let index = risk_level.index();
+            self.active_loans_by_level[index] = self.active_loans_by_level[index].saturating_add(1);
+            self.outstanding_by_level[index] = self.outstanding_by_level[index].saturating_add(loan.outstanding_balance);
+            loan.risk_segment = Some(risk_level.clone());
+            loan.segment_balance = loan.outstanding_balance;
+
+            loan.segment_principal = loan.principal_amount.saturating_sub(loan.principal_repaid);
+            loan.segment_fines = loan.pending_fines.saturating_add(loan.carried_fine);
+            loan.segment_at_risk = loan.non_performing;
+            self.outstanding_principal = self.outstanding_principal.saturating_add(loan.segment_principal);
+            self.outstanding_fines = self.outstanding_fines.saturating_add(loan.segment_fines);
+            if loan.segment_at_risk {
+                self.at_risk_outstanding = self.at_risk_outstanding.saturating_add(loan.segment_balance);
+            }
+        }
+    }
+
+    pub fn grace_period_seconds(&self) -> i64 {
+        self.grace_period_days as i64 * SECONDS_PER_DAY
+    }
+
+    /// Whether borrowers with this employment type are barred from new loans
+    pub fn is_employment_blocked(&self, employment_type: &EmploymentType) -> bool {
+        self.blocked_employment_types & employment_type.bit() != 0
+    }
+
+    /// Risk level implied by a credit score under the configured cutoffs
+    pub fn risk_level_for(&self, credit_score: u16) -> RiskLevel {
+        let [low, medium, high] = self.risk_level_cutoffs;
+        if credit_score >= low {
+            RiskLevel::Low
+        } else if credit_score >= medium {
+            RiskLevel::Medium
+        } else if credit_score >= high {
+            RiskLevel::High
+        } else {
+            RiskLevel::Critical
+        }
+    }
+}
+
+/// Interest-rate range (basis points) allowed for borrowers at or above `min_score`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RateBand {
+    pub min_score: u16,
+    pub min_rate: u16,
+    pub max_rate: u16,
+}
+
+impl RateBand {
+    pub const LEN: usize = 2 + 2 + 2;
 }
 
 /// User profile stored on-chain
@@ -36,12 +423,217 @@ pub struct UserProfile {
     pub risk_level: RiskLevel,
     pub registration_timestamp: i64,
     pub last_updated: i64,
+    /// Sum of monthly installments across the user's active loans
+    pub monthly_obligations: u64,
+    /// One-time financial-literacy credit has been applied
+    pub education_completed: bool,
+    /// Commitment to contact details held off-chain; never the details themselves
+    pub contact_hash: [u8; 32],
+    pub notification_opt_in: bool,
+    /// Account that funded this profile's rent and is owed it back on close
+    pub rent_payer: Pubkey,
+    /// Income updates made in the window starting at income_change_window_start
+    pub income_change_count: u8,
+    pub income_change_window_start: i64,
+    /// Second signer on a joint profile; either authority may act on it
+    pub co_authority: Option<Pubkey>,
+    /// Score points gained and lost in the window starting at score_window_start
+    pub score_window_start: i64,
+    pub score_window_gain: u16,
+    pub score_window_loss: u16,
+    /// Installments settled inside their own period but before the due date
+    pub early_payments: u16,
+    /// When the borrower last completed a loan and the score bonus it actually earned,
+    /// kept so a quick default can claw the bonus back
+    pub last_completion_at: i64,
+    pub last_completion_bonus: u16,
+    /// Monthly installments this user guarantees on other borrowers' active loans,
+    /// in proportion to the share of each loan guaranteed
+    pub guaranteed_obligations: u64,
+    /// Active loans this user currently guarantees, capped by max_guarantees
+    pub guaranteed_active_loans: u8,
     pub bump: u8,
 }
 
 impl UserProfile {
     pub const MAX_NAME_LEN: usize = 100;
-    pub const LEN: usize = 8 + 32 + (4 + Self::MAX_NAME_LEN) + 8 + 1 + 2 + 1 + 2 + 1 + 8 + 8 + 2 + 2 + 2 + 2 + 1 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + (4 + Self::MAX_NAME_LEN) + 8 + 1 + 2 + 1 + 2 + 1 + 8 + 8 + 2 + 2 + 2 + 2 + 1 + 8 + 8 + 8 + 1 + 32 + 1 + 32 + 1 + 8 + (1 + 32) + 8 + 2 + 2 + 2 + 8 + 2 + 8 + 1 + 1;
+
+    // Score improvement tips; each flag mirrors a rule that moves credit_score
+    /// Late installments cost points; on-time ones earn them back
+    pub const TIP_HAS_LATE_PAYMENTS: u8 = 1 << 0;
+    /// Missed installments carry missed_payment_penalty
+    pub const TIP_HAS_MISSED_PAYMENTS: u8 = 1 << 1;
+    /// Obligations above max_dti_bps of income; new loans are refused until this drops
+    pub const TIP_HIGH_UTILIZATION: u8 = 1 << 2;
+    /// Completing a loan earns completion_bonus
+    pub const TIP_NO_COMPLETED_LOANS: u8 = 1 << 3;
+    /// A default on record; recoveries claw back part of default_penalty
+    pub const TIP_HAS_DEFAULT: u8 = 1 << 4;
+    /// The one-time financial education boost is still available
+    pub const TIP_EDUCATION_AVAILABLE: u8 = 1 << 5;
+
+    /// Debt-to-income ratio in basis points, or None when income is zero
+    pub fn debt_to_income_bps(&self) -> Option<u64> {
+        self.debt_to_income_bps_with(0)
+    }
+
+    /// Debt-to-income ratio if `additional_obligation` were added to current commitments
+    pub fn debt_to_income_bps_with(&self, additional_obligation: u64) -> Option<u64> {
+        if self.monthly_income == 0 {
+            return None;
+        }
+        let obligations = (self.monthly_obligations as u128) + (additional_obligation as u128);
+        let dti = obligations * 10000 / (self.monthly_income as u128);
+        Some(dti.min(u64::MAX as u128) as u64)
+    }
+
+    /// Share of guaranteed obligations that counts against this borrower's capacity
+    pub fn counted_guarantees(&self, program_state: &LoanProgramState) -> u64 {
+        ((self.guaranteed_obligations as u128) * (program_state.guarantee_dti_weight_bps as u128) / 10000) as u64
+    }
+
+    /// Start counting a guarantee against this guarantor while the loan it backs is active
+    pub fn take_on_guarantee(&mut self, guarantor: &Guarantor) -> Result<()> {
+        self.guaranteed_obligations = self.guaranteed_obligations.checked_add(guarantor.monthly_obligation)
+            .ok_or(LoanError::MathOverflow)?;
+        self.guaranteed_active_loans = self.guaranteed_active_loans.checked_add(1)
+            .ok_or(LoanError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Stop counting a guarantee once the loan it backs leaves Active
+    pub fn release_guarantee(&mut self, guarantor: &Guarantor) {
+        self.guaranteed_obligations = self.guaranteed_obligations.saturating_sub(guarantor.monthly_obligation);
+        self.guaranteed_active_loans = self.guaranteed_active_loans.saturating_sub(1);
+    }
+
+    /// Deterministic risk score from the profile's own counters, on the credit_score scale.
+    /// Starting from the 500 a new borrower gets:
+    /// - payment history: +4 per on-time installment (max +120), -10 per late one (max -100),
+    ///   -20 per missed one (max -120)
+    /// - defaults: -100 per defaulted loan (max -200)
+    /// - repayment: up to +80 in proportion to total_repaid / total_borrowed
+    /// - completed loans: +15 each (max +60)
+    /// - employment: salaried +40, business owner +30, self-employed +20, daily wage 0, unemployed -40
+    /// - income: +40 from 100k PKR a month, +25 from 50k, +10 from 20k
+    pub fn computed_risk_score(&self) -> u16 {
+        const PKR: u64 = 1_000_000_000;
+
+        let mut score: i64 = 500;
+        score += (self.on_time_payments as i64 * 4).min(120);
+        score -= (self.late_payments as i64 * 10).min(100);
+        score -= (self.missed_payments as i64 * 20).min(120);
+        score -= (self.defaulted_loans as i64 * 100).min(200);
+        if self.total_borrowed > 0 {
+            let repaid_bps = ((self.total_repaid as u128) * 10000 / (self.total_borrowed as u128)).min(10000);
+            score += (repaid_bps * 80 / 10000) as i64;
+        }
+        score += (self.completed_loans as i64 * 15).min(60);
+        score += match self.employment_type {
+            EmploymentType::Salaried => 40,
+            EmploymentType::BusinessOwner => 30,
+            EmploymentType::SelfEmployed => 20,
+            EmploymentType::DailyWage => 0,
+            EmploymentType::Unemployed => -40,
+        };
+        score += match self.monthly_income / PKR {
+            100_000.. => 40,
+            50_000.. => 25,
+            20_000.. => 10,
+            _ => 0,
+        };
+
+        score.clamp(LoanProgramState::MIN_CREDIT_SCORE as i64, LoanProgramState::MAX_CREDIT_SCORE as i64) as u16
+    }
+
+    /// Bitmask of TIP_* factors the borrower can act on to raise their score
+    pub fn score_improvement_tips(&self, program_state: &LoanProgramState) -> u8 {
+        let mut tips = 0;
+        if self.late_payments > 0 {
+            tips |= Self::TIP_HAS_LATE_PAYMENTS;
+        }
+        if self.missed_payments > 0 {
+            tips |= Self::TIP_HAS_MISSED_PAYMENTS;
+        }
+        if self.debt_to_income_bps().is_some_and(|dti| dti > program_state.max_dti_bps as u64) {
+            tips |= Self::TIP_HIGH_UTILIZATION;
+        }
+        if self.completed_loans == 0 && program_state.completion_bonus > 0 {
+            tips |= Self::TIP_NO_COMPLETED_LOANS;
+        }
+        if self.defaulted_loans > 0 {
+            tips |= Self::TIP_HAS_DEFAULT;
+        }
+        if !self.education_completed && program_state.education_score_boost > 0 {
+            tips |= Self::TIP_EDUCATION_AVAILABLE;
+        }
+        tips
+    }
+
+    /// Move credit_score by `delta` within the score range and what's left of this window's
+    /// allowance in that direction; returns the change actually applied
+    pub fn adjust_credit_score(&mut self, delta: i32, program_state: &LoanProgramState, now: i64) -> i32 {
+        if now - self.score_window_start >= program_state.score_change_window_seconds {
+            self.score_window_start = now;
+            self.score_window_gain = 0;
+            self.score_window_loss = 0;
+        }
+
+        let cap = program_state.max_score_change_per_window as i32;
+        let delta = match (cap, delta > 0) {
+            (0, _) => delta,
+            (_, true) => delta.min(cap - self.score_window_gain as i32).max(0),
+            (_, false) => delta.max(self.score_window_loss as i32 - cap).min(0),
+        };
+
+        let old_score = self.credit_score as i32;
+        let new_score = (old_score + delta).clamp(
+            LoanProgramState::MIN_CREDIT_SCORE as i32,
+            LoanProgramState::MAX_CREDIT_SCORE as i32,
+        );
+        let applied = new_score - old_score;
+        self.credit_score = new_score as u16;
+        if applied > 0 {
+            self.score_window_gain = self.score_window_gain.saturating_add(applied as u16);
+        } else {
+            self.score_window_loss = self.score_window_loss.saturating_add(applied.unsigned_abs() as u16);
+        }
+        applied
+    }
+
+    /// Take back a change adjust_credit_score applied at `applied_at`. The cap doesn't
+    /// apply to an undo, and one made in the current window returns its allowance
+    pub fn revert_score_change(&mut self, applied: i32, applied_at: i64) {
+        let old_score = self.credit_score as i32;
+        self.credit_score = (old_score - applied).clamp(
+            LoanProgramState::MIN_CREDIT_SCORE as i32,
+            LoanProgramState::MAX_CREDIT_SCORE as i32,
+        ) as u16;
+        if applied_at < self.score_window_start {
+            return;
+        }
+        if applied > 0 {
+            self.score_window_gain = self.score_window_gain.saturating_sub(applied as u16);
+        } else {
+            self.score_window_loss = self.score_window_loss.saturating_sub(applied.unsigned_abs() as u16);
+        }
+    }
+
+    /// The primary authority, or the co-authority on a joint profile
+    pub fn is_authorized(&self, signer: Pubkey) -> bool {
+        signer == self.authority || self.co_authority == Some(signer)
+    }
+
+    /// Re-derive risk_level from the current credit score so the two never drift apart
+    pub fn sync_risk_level(&mut self, program_state: &LoanProgramState) {
+        self.risk_level = program_state.risk_level_for(self.credit_score);
+    }
+
+    /// No defaults on record and a risk level no worse than Medium
+    pub fn in_good_standing(&self) -> bool {
+        self.defaulted_loans == 0 && self.risk_level <= RiskLevel::Medium
+    }
 }
 
 /// Loan account storing loan details
@@ -53,21 +645,683 @@ pub struct Loan {
     pub interest_rate: u16,
     pub tenure_months: u8,
     pub monthly_installment: u64,
+    /// What this loan adds to the borrower's monthly_obligations: the level EMI, or the largest step
+    pub monthly_obligation: u64,
+    /// Amount due on the last installment; absorbs the rounding remainder of the EMI
+    pub final_installment: u64,
     pub total_amount: u64,
     pub outstanding_balance: u64,
     pub total_repaid: u64,
     pub total_fines: u64,
+    /// Fines assessed on part-paid installments and not settled yet
+    pub pending_fines: u64,
+    /// Fines carried past a restructure; owed with installment schedule_offset+1
+    pub carried_fine: u64,
     pub start_timestamp: i64,
     pub end_timestamp: i64,
     pub status: LoanStatus,
     pub created_timestamp: i64,
     pub completed_timestamp: Option<i64>,
     pub defaulted_timestamp: Option<i64>,
+    /// Number of servicing notes attached; also the index of the next note
+    pub note_count: u16,
+    pub installments_paid: u8,
+    /// Snapshot at origination: borrower had no completed or defaulted loans
+    pub is_first_loan: bool,
+    /// Timestamp of the most recent payment (0 = none yet)
+    pub last_payment_at: i64,
+    /// Installment the most recent payment went to; min_payment_gap_seconds only spaces repeats on it
+    pub last_payment_installment: u8,
+    /// Amount collected after the loan defaulted
+    pub total_recovered: u64,
+    /// Upfront credit-life premium (0 = uninsured)
+    pub insurance_premium: u64,
+    /// Amount the insurance pool paid against this loan after default
+    pub insurance_claimed: u64,
+    /// Origination fee withheld from the disbursement; the schedule is on the full principal
+    pub origination_fee: u64,
+    /// Servicing fee fixed at origination and due with every installment, separate from fines
+    pub servicing_fee_per_installment: u64,
+    pub total_servicing_fees: u64,
+    /// Risk level this loan is counted under in the program's segment stats, and the balance counted there
+    pub risk_segment: Option<RiskLevel>,
+    pub segment_balance: u64,
+    /// Principal, fines and non-performing flag counted in the portfolio totals at the last sync
+    pub segment_principal: u64,
+    pub segment_fines: u64,
+    pub segment_at_risk: bool,
+    /// Repayments broken into the amortized principal and interest they retired under the current schedule
+    pub principal_repaid: u64,
+    pub interest_repaid: u64,
+    /// Installments follow this loan's StepSchedule rather than the level EMI
+    pub is_stepped: bool,
+    /// Rate reduction applied at origination for a high credit score
+    pub rate_discount_bps: u16,
+    /// Installment the last reminder was sent for, and the ladder stage it reached (1-based)
+    pub last_reminder_installment: u8,
+    pub last_reminder_stage: u8,
+    /// Account that funded this loan's rent and is owed it back on close
+    pub rent_payer: Pubkey,
+    /// Overdue past npl_days but not yet defaulted; cleared by catching up
+    pub non_performing: bool,
+    /// Bit n-1 set once installment n has been counted as missed
+    pub missed_installments: u64,
+    /// Bit n-1 set while installment n stands paid
+    pub paid_installments: u64,
+    /// Installment whose payment reverse_payment may undo next (0 = none); each
+    /// PaymentRecord keeps the one before it, so reversals unwind in payment order
+    pub reversible_installment: u8,
+    pub cancelled_timestamp: Option<i64>,
+    /// A Collateral account has been locked against this loan
+    pub is_secured: bool,
+    /// A Guarantor account backs this loan
+    pub has_guarantor: bool,
+    /// Times the loan has been re-amortized
+    pub restructure_count: u8,
+    /// Installments settled before the current schedule began; the schedule's
+    /// principal_amount is amortized over installments schedule_offset+1..=tenure_months
+    pub schedule_offset: u8,
     pub bump: u8,
 }
 
 impl Loan {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 2 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + (1 + 8) + (1 + 8) + 1;
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 2 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + (1 + 8) + (1 + 8) + 2 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 2 + 1 + 1 + 32 + 1 + 8 + (1 + 8) + 1 + 1 + 1 + 1 + 8 + 8 + 8 + (1 + 1) + 8 + 8 + 8 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 1;
+
+    /// Upper bound on periods simulated when projecting a payoff
+    pub const MAX_PROJECTION_PERIODS: u16 = 600;
+
+    /// Installments returned per amortization schedule page; 24 entries stay well inside return data
+    pub const MAX_SCHEDULE_PAGE: u8 = 24;
+
+    /// The status enum is the source of truth: an active loan carries no
+    /// termination timestamp, and a completed or defaulted loan carries only its own
+    pub fn validate_status_timestamps(&self) -> Result<()> {
+        let consistent = match self.status {
+            LoanStatus::Active => {
+                self.completed_timestamp.is_none()
+                    && self.defaulted_timestamp.is_none()
+                    && self.cancelled_timestamp.is_none()
+            }
+            LoanStatus::Completed => {
+                self.completed_timestamp.is_some()
+                    && self.defaulted_timestamp.is_none()
+                    && self.cancelled_timestamp.is_none()
+            }
+            LoanStatus::Defaulted => {
+                self.defaulted_timestamp.is_some()
+                    && self.completed_timestamp.is_none()
+                    && self.cancelled_timestamp.is_none()
+            }
+            LoanStatus::Cancelled => {
+                self.cancelled_timestamp.is_some()
+                    && self.completed_timestamp.is_none()
+                    && self.defaulted_timestamp.is_none()
+            }
+        };
+        require!(consistent, LoanError::InvalidStatusTransition);
+        Ok(())
+    }
+
+    /// Written-off balance that recoveries and insurance haven't covered yet
+    pub fn unrecovered_balance(&self) -> u64 {
+        self.outstanding_balance
+            .saturating_sub(self.total_recovered)
+            .saturating_sub(self.insurance_claimed)
+    }
+
+    /// When a completed or cancelled loan reached that state; None for any other status
+    pub fn terminal_timestamp(&self) -> Option<i64> {
+        match self.status {
+            LoanStatus::Completed => self.completed_timestamp,
+            LoanStatus::Cancelled => self.cancelled_timestamp,
+            _ => None,
+        }
+    }
+
+    /// Terminal loans become reclaimable once the retention period has fully passed
+    pub fn is_closeable(&self, now: i64, retention_period_seconds: i64) -> bool {
+        self.terminal_timestamp()
+            .is_some_and(|ended_at| now - ended_at > retention_period_seconds)
+    }
+
+    /// Due date of the oldest unpaid installment; 0 once the loan is closed or fully paid.
+    /// installments_paid counts the payment records settled so far.
+    pub fn next_due_timestamp(&self) -> i64 {
+        let next_installment = self.installments_paid.saturating_add(1);
+        if self.status == LoanStatus::Active && next_installment <= self.tenure_months {
+            self.due_timestamp(next_installment)
+        } else {
+            0
+        }
+    }
+
+    /// Due date of an installment
+    pub fn due_timestamp(&self, installment_number: u8) -> i64 {
+        self.start_timestamp + (installment_number as i64) * SECONDS_PER_PERIOD
+    }
+
+    /// Rapid repeat payments on one installment are rejected to stop wash activity on the
+    /// on-time counters; paying a different installment is never held back
+    pub fn check_payment_gap(&self, installment_number: u8, program_state: &LoanProgramState, now: i64) -> Result<()> {
+        if program_state.min_payment_gap_seconds > 0
+            && self.last_payment_at > 0
+            && self.last_payment_installment == installment_number
+        {
+            require!(
+                now - self.last_payment_at >= program_state.min_payment_gap_seconds,
+                LoanError::PaymentTooFrequent
+            );
+        }
+        Ok(())
+    }
+
+    /// Paid before the due date but within the installment's own period; paying
+    /// further ahead doesn't count, so installments can't be prepaid for the bonus
+    pub fn is_early(&self, installment_number: u8, now: i64) -> bool {
+        let due = self.due_timestamp(installment_number);
+        now < due && now >= due - SECONDS_PER_PERIOD
+    }
+
+    /// Whole days the oldest unpaid installment is past its due date (0 if none is)
+    pub fn days_past_due(&self, now: i64) -> i64 {
+        if self.installments_paid >= self.tenure_months {
+            return 0;
+        }
+        let oldest_unpaid = self.installments_paid + 1;
+        (now - self.due_timestamp(oldest_unpaid)).max(0) / SECONDS_PER_DAY
+    }
+
+    /// Last moment an installment can be paid without counting as late
+    pub fn grace_ends_at(&self, installment_number: u8, program_state: &LoanProgramState) -> i64 {
+        self.due_timestamp(installment_number) + self.grace_period_seconds(installment_number, program_state)
+    }
+
+    /// Grace for an installment; the final one can be given longer to close out
+    pub fn grace_period_seconds(&self, installment_number: u8, program_state: &LoanProgramState) -> i64 {
+        if installment_number == self.tenure_months && program_state.final_installment_grace_days > 0 {
+            program_state.final_installment_grace_days as i64 * SECONDS_PER_DAY
+        } else {
+            program_state.grace_period_seconds()
+        }
+    }
+
+    /// Scheduled amount due for an installment (excluding fines).
+    /// The last installment is capped at whatever balance remains.
+    pub fn installment_due(&self, installment_number: u8) -> u64 {
+        if installment_number == self.tenure_months {
+            self.final_installment.min(self.outstanding_balance)
+        } else {
+            self.monthly_installment
+        }
+    }
+
+    /// installment_due for a loan that may be stepped; the step schedule must be
+    /// passed exactly when the loan has one
+    pub fn scheduled_due(&self, installment_number: u8, step_schedule: Option<&StepSchedule>) -> Result<u64> {
+        require!(self.is_stepped == step_schedule.is_some(), LoanError::StepScheduleMismatch);
+        Ok(match step_schedule {
+            Some(step_schedule) if installment_number == self.tenure_months => {
+                step_schedule.amount(installment_number).min(self.outstanding_balance)
+            }
+            Some(step_schedule) => step_schedule.amount(installment_number),
+            None => self.installment_due(installment_number),
+        })
+    }
+
+    /// Scheduled installment before the final one's balance cap
+    pub fn scheduled_amount(&self, installment_number: u8, step_schedule: Option<&StepSchedule>) -> Result<u64> {
+        require!(self.is_stepped == step_schedule.is_some(), LoanError::StepScheduleMismatch);
+        Ok(match step_schedule {
+            Some(step_schedule) => step_schedule.amount(installment_number),
+            None if installment_number == self.tenure_months => self.final_installment,
+            None => self.monthly_installment,
+        })
+    }
+
+    /// Principal and interest of a scheduled installment, stepped or level
+    pub fn installment_split(&self, installment_number: u8, step_schedule: Option<&StepSchedule>) -> Result<(u64, u64)> {
+        require!(self.is_stepped == step_schedule.is_some(), LoanError::StepScheduleMismatch);
+        match step_schedule {
+            Some(step_schedule) => Ok(step_schedule.split(self, installment_number)),
+            None => {
+                let split = self.amortization_split(installment_number, None)?;
+                Ok((split.principal_component, split.interest_component))
+            }
+        }
+    }
+
+    /// Interest accrued on a principal balance over one monthly period
+    pub fn period_interest(&self, balance: u64) -> u64 {
+        ((balance as u128) * (self.interest_rate as u128) / 12 / 10000) as u64
+    }
+
+    /// Reducing-balance split of a scheduled installment, following the step
+    /// schedule when the loan has one. The final installment retires whatever
+    /// principal remains so the schedule always ends at zero.
+    pub fn amortization_split(
+        &self,
+        installment_number: u8,
+        step_schedule: Option<&StepSchedule>,
+    ) -> Result<AmortizationSplit> {
+        require!(self.is_stepped == step_schedule.is_some(), LoanError::StepScheduleMismatch);
+        require!(
+            installment_number > self.schedule_offset && installment_number <= self.tenure_months,
+            LoanError::InvalidInstallmentNumber
+        );
+
+        let mut balance = self.principal_amount;
+        let mut split = AmortizationSplit::default();
+
+        for number in (self.schedule_offset + 1)..=installment_number {
+            let (principal_component, interest_component) = self.split_at_balance(number, balance, step_schedule);
+            balance -= principal_component;
+
+            split = AmortizationSplit {
+                installment_number: number,
+                principal_component,
+                interest_component,
+                balance_after: balance,
+            };
+        }
+
+        Ok(split)
+    }
+
+    /// Due dates and reducing-balance splits for a window of the schedule,
+    /// starting at `start_installment` and clamped to MAX_SCHEDULE_PAGE entries
+    /// so the result fits in return data; page through longer tenures
+    pub fn amortization_schedule(
+        &self,
+        start_installment: u8,
+        count: u8,
+        step_schedule: Option<&StepSchedule>,
+    ) -> Result<Vec<InstallmentDue>> {
+        require!(self.is_stepped == step_schedule.is_some(), LoanError::StepScheduleMismatch);
+        require!(
+            start_installment > self.schedule_offset && start_installment <= self.tenure_months,
+            LoanError::InvalidInstallmentNumber
+        );
+        require!(count > 0, LoanError::InvalidInstallmentNumber);
+
+        let end = start_installment
+            .saturating_add(count.min(Self::MAX_SCHEDULE_PAGE) - 1)
+            .min(self.tenure_months);
+        let mut balance = self.principal_amount;
+        let mut schedule = Vec::with_capacity((end - start_installment + 1) as usize);
+
+        for number in (self.schedule_offset + 1)..=end {
+            let (principal_portion, interest_portion) = self.split_at_balance(number, balance, step_schedule);
+            balance -= principal_portion;
+
+            if number >= start_installment {
+                schedule.push(InstallmentDue {
+                    number,
+                    due_timestamp: self.due_timestamp(number),
+                    principal_portion,
+                    interest_portion,
+                    running_balance: balance,
+                });
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    /// Principal and interest of an installment given the principal still owed before it
+    fn split_at_balance(&self, installment_number: u8, balance: u64, step_schedule: Option<&StepSchedule>) -> (u64, u64) {
+        if let Some(step_schedule) = step_schedule {
+            return step_schedule.split_at_balance(self, installment_number, balance);
+        }
+        if installment_number == self.tenure_months {
+            (balance, self.final_installment.saturating_sub(balance))
+        } else {
+            let interest = self.period_interest(balance);
+            let principal = self.monthly_installment.saturating_sub(interest).min(balance);
+            (principal, self.monthly_installment - principal)
+        }
+    }
+
+    /// Lateness and fine for paying an installment at `now`, including the
+    /// first-loan discount. This is the single source for record_payment and views.
+    pub fn assess_lateness(
+        &self,
+        installment_number: u8,
+        now: i64,
+        program_state: &LoanProgramState,
+    ) -> Result<LateAssessment> {
+        let on_time = now <= self.grace_ends_at(installment_number, program_state);
+        let days_late = days_late_after_grace(
+            self.due_timestamp(installment_number),
+            now,
+            self.grace_period_seconds(installment_number, program_state),
+        );
+        let mut fine_amount = compute_fine(
+            self.monthly_installment,
+            days_late,
+            program_state.daily_fine_bps,
+            program_state.fine_cap_bps,
+            program_state.min_fine_cap,
+        );
+
+        // First-time borrowers get a gentler fine to build repayment habits
+        if self.is_first_loan && fine_amount > 0 {
+            let discount = (fine_amount as u128)
+                .checked_mul(program_state.first_loan_fine_discount_bps as u128)
+                .ok_or(LoanError::MathOverflow)?
+                / 10000;
+            fine_amount -= discount as u64;
+        }
+
+        // Fines carried past a restructure fall due with the new schedule's first installment
+        if installment_number == self.schedule_offset + 1 {
+            fine_amount = fine_amount.saturating_add(self.carried_fine);
+        }
+
+        // Consumer-protection cap: fines stop accruing once they'd push the loan past it
+        if let Some(cap) = self.liability_cap(program_state) {
+            let headroom = cap
+                .saturating_sub(self.total_amount)
+                .saturating_sub(self.total_fines);
+            fine_amount = fine_amount.min(headroom);
+        }
+
+        Ok(LateAssessment { on_time, days_late, fine_amount })
+    }
+
+    /// Fines owed right now across every unpaid installment, carried fines included
+    pub fn accrued_fines(&self, now: i64, program_state: &LoanProgramState) -> Result<u64> {
+        let mut fines = 0u64;
+        for number in (self.schedule_offset + 1)..=self.tenure_months {
+            if self.paid_installments & (1u64 << (number - 1)) == 0 {
+                fines = fines.saturating_add(self.assess_lateness(number, now, program_state)?.fine_amount);
+            }
+        }
+        // Each assessment sees the same headroom, so the sum needs capping too
+        if let Some(cap) = self.liability_cap(program_state) {
+            fines = fines.min(cap.saturating_sub(self.total_amount).saturating_sub(self.total_fines));
+        }
+        Ok(fines)
+    }
+
+    /// Most a borrower can ever owe on this loan (schedule plus fines), if capped
+    pub fn liability_cap(&self, program_state: &LoanProgramState) -> Option<u64> {
+        if program_state.max_total_repayment_multiplier_bps == 0 {
+            return None;
+        }
+        let cap = (self.principal_amount as u128)
+            * (program_state.max_total_repayment_multiplier_bps as u128)
+            / 10000;
+        Some(cap.min(u64::MAX as u128) as u64)
+    }
+
+    /// Principal still owed after the installments paid so far
+    pub fn remaining_principal(&self) -> Result<u64> {
+        // Stepped installments don't follow the level split, but every one is booked as it's paid
+        if self.is_stepped {
+            return Ok(self.principal_amount.saturating_sub(self.principal_repaid));
+        }
+        if self.installments_paid <= self.schedule_offset {
+            return Ok(self.principal_amount);
+        }
+        Ok(self.amortization_split(self.installments_paid.min(self.tenure_months), None)?.balance_after)
+    }
+
+    /// Interest scheduled in the remaining installments, of which a configured share is forgiven on prepayment
+    pub fn prepayment_rebate(&self, program_state: &LoanProgramState) -> Result<u64> {
+        let unearned_interest = self.outstanding_balance.saturating_sub(self.remaining_principal()?);
+        let rebate = (unearned_interest as u128)
+            .checked_mul(program_state.prepayment_rebate_bps as u128)
+            .ok_or(LoanError::MathOverflow)?
+            / 10000;
+        Ok(rebate as u64)
+    }
+
+    /// Simulate the remaining amortization at a fixed monthly payment
+    pub fn project_payoff(&self, monthly_payment: u64) -> Result<PayoffProjection> {
+        let mut balance = self.remaining_principal()?;
+        let scheduled_remaining = self.outstanding_balance;
+        let scheduled_interest = scheduled_remaining.saturating_sub(balance);
+
+        let mut periods: u16 = 0;
+        let mut projected_interest: u64 = 0;
+        while balance > 0 {
+            let interest = self.period_interest(balance);
+            if monthly_payment <= interest || periods >= Self::MAX_PROJECTION_PERIODS {
+                return Ok(PayoffProjection::default());
+            }
+            balance = balance.saturating_sub(monthly_payment - interest);
+            projected_interest = projected_interest.saturating_add(interest);
+            periods += 1;
+        }
+
+        let last_installment = (self.installments_paid as i64) + (periods as i64);
+        Ok(PayoffProjection {
+            pays_off: true,
+            estimated_installments_remaining: periods,
+            estimated_payoff_timestamp: self.start_timestamp + last_installment * SECONDS_PER_PERIOD,
+            total_interest_saved: scheduled_interest.saturating_sub(projected_interest),
+        })
+    }
+}
+
+/// Outcome of checking an installment payment against its due date
+pub struct LateAssessment {
+    pub on_time: bool,
+    pub days_late: u16,
+    pub fine_amount: u64,
+}
+
+/// Everything about a loan in one read: terms, health, progress and borrower standing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoanFullView {
+    pub loan_id: u64,
+    pub user: Pubkey,
+    pub status: LoanStatus,
+    pub principal_amount: u64,
+    pub interest_rate: u16,
+    pub tenure_months: u8,
+    pub monthly_installment: u64,
+    pub final_installment: u64,
+    pub total_amount: u64,
+    pub outstanding_balance: u64,
+    pub total_repaid: u64,
+    pub total_fines: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub installments_paid: u8,
+    /// Due date of the next unpaid installment (0 when none remain)
+    pub next_due_timestamp: i64,
+    pub overdue: bool,
+    pub days_past_due: u16,
+    /// Fine the next installment would carry if paid now
+    pub accrued_fine: u64,
+    /// Share of total_amount repaid, in basis points
+    pub repayment_progress_bps: u16,
+    pub principal_repaid_to_date: u64,
+    pub interest_repaid_to_date: u64,
+    pub credit_score: u16,
+    pub risk_level: RiskLevel,
+}
+
+/// Every tunable program parameter, with the fixed pricing constants clients validate against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProgramConfigView {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub fee_percentage: u16,
+    pub paused: bool,
+    pub max_dti_bps: u16,
+    pub escalate_risk_on_dti_breach: bool,
+    pub allow_neg_am: bool,
+    pub anomaly_detection_enabled: bool,
+    pub anomaly_window_seconds: i64,
+    pub anomaly_max_loans: u32,
+    pub anomaly_max_volume: u64,
+    pub first_loan_fine_discount_bps: u16,
+    pub min_payment_gap_seconds: i64,
+    pub rate_bands: [RateBand; 4],
+    pub max_profile_age_for_loan: i64,
+    pub completion_bonus: u16,
+    pub default_penalty: u16,
+    pub borrower_wallet_program: Pubkey,
+    pub insurance_premium_bps: u16,
+    pub discount_score_threshold: u16,
+    pub score_discount_bps: u16,
+    pub discount_rate_floor: u16,
+    pub reminder_ladder_offsets: [i64; 5],
+    pub rate_oracle: Pubkey,
+    pub oracle_spread_bps: u16,
+    pub education_score_boost: u16,
+    pub grace_after_maturity_days: u16,
+    pub max_total_repayment_multiplier_bps: u16,
+    pub min_income_for_recommendation: u64,
+    pub min_spread_bps: u16,
+    pub max_spread_bps: u16,
+    pub risk_level_cutoffs: [u16; 3],
+    pub min_installment_amount: u64,
+    pub risk_multipliers: [u16; 4],
+    pub npl_days: u16,
+    pub npl_penalty: u16,
+    pub completion_dust_tolerance: u64,
+    pub max_income_changes_per_window: u8,
+    pub income_change_window_seconds: i64,
+    pub reject_excess_income_changes: bool,
+    pub min_account_age_for_loan: i64,
+    pub missed_payment_penalty: u16,
+    pub retention_period_seconds: i64,
+    pub prepayment_rebate_bps: u16,
+    pub max_users: u64,
+    pub final_installment_grace_days: u8,
+    pub blocked_employment_types: u8,
+    pub borrower_friendly_rounding: bool,
+    pub max_loans_per_slot: u16,
+    pub guarantor_default_penalty: u16,
+    pub allow_fine_only_payments: bool,
+    pub max_score_change_per_window: u16,
+    pub score_change_window_seconds: i64,
+    pub require_risk_profile: bool,
+    pub max_restructures_per_loan: u8,
+    pub fine_cap_bps: u16,
+    pub min_fine_cap: u64,
+    pub early_payment_bonus: u16,
+    pub max_active_loans: u8,
+    pub max_default_probability_for_loan: u16,
+    pub settle_fines_on_chain: bool,
+    pub servicing_fee_per_installment: u64,
+    pub credit_line_min_completed_loans: u8,
+    pub bonus_clawback_window_seconds: i64,
+    pub max_start_backdate_seconds: i64,
+    pub max_start_lead_seconds: i64,
+    pub guarantee_dti_weight_bps: u16,
+    pub max_guarantees: u8,
+    pub fee_refund_window_days: u16,
+    pub post_unpause_cooldown_seconds: i64,
+    pub payment_waterfall: PaymentWaterfall,
+    pub missed_installments_to_default: u8,
+    pub max_interest_rate: u16,
+    pub grace_period_seconds: i64,
+    pub daily_fine_bps: u64,
+}
+
+/// Where the clock sits relative to an installment's due date and grace window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum GraceState {
+    BeforeDue,
+    InGrace,
+    Overdue,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InstallmentGraceStatus {
+    pub installment_number: u8,
+    pub due_timestamp: i64,
+    pub grace_ends_at: i64,
+    pub state: GraceState,
+    /// Zero once the grace window has closed
+    pub seconds_until_grace_end: i64,
+}
+
+/// What paying an installment right now would cost, as record_payment would charge it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CurrentFineView {
+    pub installment_number: u8,
+    pub base_due: u64,
+    pub fine: u64,
+    pub servicing_fee: u64,
+    pub total_due: u64,
+    pub days_late: u16,
+}
+
+/// Portfolio exposure by risk level, each array indexed Low..Critical
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RiskLevelStats {
+    pub active_loans: [u32; 4],
+    pub outstanding: [u64; 4],
+    pub defaults: [u32; 4],
+}
+
+/// Total money at risk across the portfolio
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PortfolioExposure {
+    pub total_outstanding_principal: u64,
+    pub total_outstanding_fines: u64,
+    pub active_loan_count: u32,
+    pub defaulted_outstanding: u64,
+    /// Outstanding balance of active loans marked non-performing
+    pub at_risk_outstanding: u64,
+}
+
+/// Compact repayment snapshot of a loan for clients that don't need the full view
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoanSummary {
+    pub outstanding_balance: u64,
+    pub total_repaid: u64,
+    pub total_fines: u64,
+    pub installments_paid: u8,
+    pub next_due_timestamp: i64,
+    pub status: LoanStatus,
+}
+
+/// A borrower's current debt-to-income position against the configured limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DtiView {
+    pub monthly_income: u64,
+    /// Sum of monthly installments across the borrower's active loans
+    pub monthly_obligations: u64,
+    /// None when monthly income is zero
+    pub dti_bps: Option<u64>,
+    pub max_dti_bps: u16,
+    pub within_limit: bool,
+}
+
+/// Result of simulating payoff at a hypothetical payment level.
+/// `pays_off` is false when the payment never outpaces the interest.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct PayoffProjection {
+    pub pays_off: bool,
+    pub estimated_installments_remaining: u16,
+    pub estimated_payoff_timestamp: i64,
+    pub total_interest_saved: u64,
+}
+
+/// Principal/interest breakdown of a single scheduled installment
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct AmortizationSplit {
+    pub installment_number: u8,
+    pub principal_component: u64,
+    pub interest_component: u64,
+    pub balance_after: u64,
+}
+
+/// One installment of the repayment schedule as the contract computes it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InstallmentDue {
+    pub number: u8,
+    pub due_timestamp: i64,
+    pub principal_portion: u64,
+    pub interest_portion: u64,
+    /// Principal still owed after this installment
+    pub running_balance: u64,
 }
 
 /// Payment record for tracking installment payments
@@ -80,14 +1334,209 @@ pub struct PaymentRecord {
     pub fine_amount: u64,
     pub payment_timestamp: i64,
     pub payment_hash: String,
+    /// How payment_hash should be interpreted
+    pub payment_ref_format: PaymentRefFormat,
     pub on_time: bool,
     pub days_late: u16,
+    /// Portion of fine_amount waived so far
+    pub fine_waived: u64,
+    /// Account that funded this record's rent and is owed it back on close
+    pub rent_payer: Pubkey,
+    /// Balance this payment retired; a reversal puts exactly this back
+    pub balance_applied: u64,
+    /// Part of fine_amount carried forward from before a restructure; a reversal owes it again
+    pub carried_fine: u64,
+    /// loan.reversible_installment before this payment; restored when it's reversed
+    pub previous_reversible: u8,
+    /// Credit score change this payment actually applied, after the window cap
+    pub score_change: i16,
     pub bump: u8,
 }
 
 impl PaymentRecord {
     pub const MAX_HASH_LEN: usize = 100;
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + (4 + Self::MAX_HASH_LEN) + 1 + 2 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + (4 + Self::MAX_HASH_LEN) + 1 + 1 + 2 + 8 + 32 + 8 + 8 + 1 + 2 + 1;
+
+    /// Fine on this record that hasn't been waived yet
+    pub fn outstanding_fine(&self) -> u64 {
+        self.fine_amount.saturating_sub(self.fine_waived)
+    }
+}
+
+/// Running total toward one installment that is being paid in parts
+#[account]
+pub struct InstallmentState {
+    pub loan: Pubkey,
+    pub installment_number: u8,
+    /// Cumulative amount paid toward this installment, fine included
+    pub amount_paid: u64,
+    /// Fine as assessed at the most recent part payment; grows while the installment stays overdue
+    pub fine_amount: u64,
+    /// Part of fine_amount already cleared by fine-only payments or the waterfall; included in amount_paid
+    pub fine_paid: u64,
+    /// Installment interest and principal the parts have settled so far, booked to the loan as they're paid
+    pub interest_paid: u64,
+    pub principal_paid: u64,
+    pub last_payment_at: i64,
+    /// Set once amount_paid covers the installment plus fine
+    pub completed: bool,
+    pub on_time: bool,
+    pub days_late: u16,
+    /// Account that funded this tracker's rent and is owed it back on close
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+}
+
+impl InstallmentState {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 2 + 32 + 1;
+}
+
+/// Asset pledged against a secured loan
+#[account]
+pub struct Collateral {
+    pub loan: Pubkey,
+    pub collateral_type: CollateralType,
+    /// Value declared at lock time; not independently appraised on-chain
+    pub declared_value: u64,
+    /// Held against the loan; cleared when released after completion or cancellation
+    pub locked: bool,
+    /// Taken by the lender when the loan defaulted
+    pub seized: bool,
+    pub locked_at: i64,
+    pub bump: u8,
+}
+
+impl Collateral {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 1 + 1 + 8 + 1;
+
+    /// Still locked for the borrower: neither released nor seized
+    pub fn is_held(&self) -> bool {
+        self.locked && !self.seized
+    }
+}
+
+/// Registered user who co-signs a loan and answers for it on default
+#[account]
+pub struct Guarantor {
+    pub loan: Pubkey,
+    pub guarantor: Pubkey,
+    /// The guarantor's own UserProfile
+    pub guarantor_profile: Pubkey,
+    pub guaranteed_amount: u64,
+    /// Guaranteed share of the loan's monthly installment, fixed when the guarantee is added
+    pub monthly_obligation: u64,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl Guarantor {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1;
+}
+
+/// Timestamped servicing note attached to a loan; written once, never edited
+#[account]
+pub struct LoanNote {
+    pub loan: Pubkey,
+    pub author: Pubkey,
+    pub note_index: u16,
+    pub timestamp: i64,
+    pub text: String,
+    pub bump: u8,
+}
+
+impl LoanNote {
+    pub const MAX_TEXT_LEN: usize = 280;
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 8 + (4 + Self::MAX_TEXT_LEN) + 1;
+}
+
+/// Floating-rate reference published by an authorized feeder
+#[account]
+pub struct RateOracle {
+    pub feeder: Pubkey,
+    pub current_rate_bps: u16,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl RateOracle {
+    pub const LEN: usize = 8 + 32 + 2 + 8 + 1;
+}
+
+/// Per-installment amounts for a stepped repayment plan, summing to the loan's total_amount
+#[account]
+pub struct StepSchedule {
+    pub loan: Pubkey,
+    pub amounts: Vec<u64>,
+    pub bump: u8,
+}
+
+impl StepSchedule {
+    pub const MAX_INSTALLMENTS: usize = 60;
+    pub const LEN: usize = 8 + 32 + (4 + 8 * Self::MAX_INSTALLMENTS) + 1;
+
+    pub fn amount(&self, installment_number: u8) -> u64 {
+        self.amounts[(installment_number - 1) as usize]
+    }
+
+    /// Reducing-balance split of a stepped installment. Interest accrues on the
+    /// principal still owed but never exceeds the installment, and the final
+    /// installment retires whatever principal remains.
+    pub fn split(&self, loan: &Loan, installment_number: u8) -> (u64, u64) {
+        let mut balance = loan.principal_amount;
+        let mut split = (0, 0);
+        for number in 1..=installment_number {
+            split = self.split_at_balance(loan, number, balance);
+            balance -= split.0;
+        }
+        split
+    }
+
+    /// Principal and interest of a stepped installment given the principal still owed before it
+    pub fn split_at_balance(&self, loan: &Loan, installment_number: u8, balance: u64) -> (u64, u64) {
+        let amount = self.amount(installment_number);
+        if installment_number == loan.tenure_months {
+            (balance, amount.saturating_sub(balance))
+        } else {
+            let interest = loan.period_interest(balance).min(amount);
+            ((amount - interest).min(balance), interest)
+        }
+    }
+
+    /// Largest step, which is what the borrower has to be able to afford
+    pub fn peak_amount(&self) -> u64 {
+        self.amounts.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// Program-owned account holding fines settled on-chain until the authority withdraws them
+#[account]
+pub struct Treasury {
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + 8 + 8 + 1;
+}
+
+/// Pre-approved limit a repeat borrower draws loans against without full underwriting
+#[account]
+pub struct CreditLine {
+    pub user: Pubkey,
+    pub limit: u64,
+    pub available: u64,
+    /// Rate every draw is booked at, fixed when the line is approved
+    pub interest_rate: u16,
+    pub total_drawn: u64,
+    pub draw_count: u16,
+    pub opened_timestamp: i64,
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+}
+
+impl CreditLine {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 2 + 8 + 2 + 8 + 32 + 1;
 }
 
 /// Risk profile for a user
@@ -100,11 +1549,33 @@ pub struct RiskProfile {
     pub recommended_max_loan: u64,
     pub last_calculated: i64,
     pub factors_count: u8,
+    /// False while income is too low to underwrite against
+    pub loan_eligible: bool,
     pub bump: u8,
 }
 
 impl RiskProfile {
-    pub const LEN: usize = 8 + 32 + 2 + 1 + 2 + 8 + 8 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 2 + 1 + 2 + 8 + 8 + 1 + 1 + 1;
+
+    /// Recommended max loan from income and this profile's risk level
+    pub fn compute_recommended_max_loan(&self, monthly_income: u64, program_state: &LoanProgramState) -> Result<u64> {
+        monthly_income
+            .checked_mul(program_state.income_multiplier(&self.risk_level))
+            .ok_or(LoanError::MathOverflow.into())
+    }
+
+    /// Refresh the recommendation and eligibility from income.
+    /// Returns true when income is below the floor and the recommendation was zeroed.
+    pub fn apply_recommendation(&mut self, monthly_income: u64, program_state: &LoanProgramState) -> Result<bool> {
+        if monthly_income < program_state.min_income_for_recommendation {
+            self.recommended_max_loan = 0;
+            self.loan_eligible = false;
+            return Ok(true);
+        }
+        self.recommended_max_loan = self.compute_recommended_max_loan(monthly_income, program_state)?;
+        self.loan_eligible = true;
+        Ok(false)
+    }
 }
 
 /// Employment type enum
@@ -117,6 +1588,16 @@ pub enum EmploymentType {
     Unemployed,
 }
 
+impl EmploymentType {
+    /// Mask with a bit set for every variant
+    pub const ALL_MASK: u8 = 0b1_1111;
+
+    /// This variant's bit in an employment-type mask
+    pub fn bit(&self) -> u8 {
+        1 << (self.clone() as u8)
+    }
+}
+
 /// Loan status enum
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum LoanStatus {
@@ -126,11 +1607,396 @@ pub enum LoanStatus {
     Cancelled,
 }
 
-/// Risk level enum
+/// Kind of external payment reference stored on a PaymentRecord
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PaymentRefFormat {
+    /// Base58 Solana transaction signature
+    TxSignature,
+    /// Bank transfer reference
+    BankRef,
+    /// Anything else, e.g. a payment processor intent id
+    Custom,
+}
+
+impl PaymentRefFormat {
+    /// Whether a reference has a plausible length for this format
+    pub fn accepts(&self, reference: &str) -> bool {
+        let len = reference.len();
+        match self {
+            // 64 bytes encode to 87 or 88 base58 characters
+            PaymentRefFormat::TxSignature => (87..=88).contains(&len),
+            PaymentRefFormat::BankRef => (1..=32).contains(&len),
+            PaymentRefFormat::Custom => len <= PaymentRecord::MAX_HASH_LEN,
+        }
+    }
+}
+
+/// Order a part payment is applied in. Full payments clear everything at once,
+/// so the order only matters while an installment is being paid in parts.
+///
+/// The choice is a consumer-protection question as much as an accounting one.
+/// Many jurisdictions require payments to go to interest before principal and
+/// restrict applying them to penalties first, since that keeps the borrower
+/// delinquent on the installment and lets fines compound. FinesFirst maximises
+/// the lender's penalty recovery and should only be enabled where the loan
+/// terms and local rules allow it. PrincipalFirst is the most borrower-friendly,
+/// reducing the principal interest is charged on, but leaves accrued interest
+/// outstanding for longer. The servicing fee always comes last.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PaymentWaterfall {
+    /// Fine, then interest, then principal
+    FinesFirst,
+    /// Interest, then principal, then the fine
+    InterestFirst,
+    /// Principal, then interest, then the fine
+    PrincipalFirst,
+}
+
+impl PaymentWaterfall {
+    /// Split a part payment across the fine, interest and principal still owed,
+    /// returned in that order; anything left over goes toward the servicing fee
+    pub fn allocate(&self, amount: u64, fine: u64, interest: u64, principal: u64) -> (u64, u64, u64) {
+        // Indexes into [fine, interest, principal]
+        let order = match self {
+            PaymentWaterfall::FinesFirst => [0, 1, 2],
+            PaymentWaterfall::InterestFirst => [1, 2, 0],
+            PaymentWaterfall::PrincipalFirst => [2, 1, 0],
+        };
+        let owed = [fine, interest, principal];
+        let mut parts = [0u64; 3];
+        let mut remaining = amount;
+        for index in order {
+            parts[index] = remaining.min(owed[index]);
+            remaining -= parts[index];
+        }
+        (parts[0], parts[1], parts[2])
+    }
+}
+
+/// What restructure_loan does with fines accrued on the old schedule. The new
+/// schedule's first installment falls due a period out, so without carrying
+/// them forward these fines stop being owed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RestructureFineHandling {
+    /// Keep them owed as fines, due with the first installment of the new schedule
+    CarryForward,
+    /// Forgive them
+    Waive,
+    /// Add them to the restructured principal
+    Capitalize,
+}
+
+/// Why a loan was moved to Defaulted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DefaultReason {
+    /// Marked by an admin
+    Manual,
+    /// Still carried a balance past maturity plus the configured grace
+    MaturityBreach,
+    /// Reached missed_installments_to_default installments marked missed
+    MissedInstallments,
+}
+
+/// Admin-gated mutation recorded in an AdminAction audit event
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdminActionCode {
+    CreateLoan,
+    OpenCreditLine,
+    CreateStepSchedule,
+    WaiveFine,
+    MarkLoanDefaulted,
+    MarkLoanCompleted,
+    CancelLoan,
+    ReversePayment,
+    CloseLoan,
+    MarkInstallmentMissed,
+    UpdateRiskScore,
+    ComputeRiskScore,
+    RefreshLoanRecommendation,
+    UpdateProgramConfig,
+    UpdateRiskMultipliers,
+    UpdateFineParams,
+    MigrateProgramState,
+    SetPauseState,
+    SetMaxUsers,
+    SetMaxActiveLoans,
+    SetEmploymentBlocklist,
+    TransferAuthority,
+    AdjustProgramCounters,
+    RecordRecovery,
+    ClaimInsurance,
+    RecordFinancialEducation,
+    InitRateOracle,
+    InitTreasury,
+    WithdrawTreasury,
+    AddLoanNote,
+    RestructureLoan,
+    LockCollateral,
+    ReleaseCollateral,
+}
+
+/// Kind of asset pledged as collateral
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum CollateralType {
+    Vehicle,
+    Property,
+    Gold,
+    Cash,
+    Other,
+}
+
+/// Risk level enum, ordered from least to most severe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum RiskLevel {
     Low,
     Medium,
     High,
     Critical,
 }
+
+impl RiskLevel {
+    /// Position in per-level arrays, Low first
+    pub fn index(&self) -> usize {
+        match self {
+            RiskLevel::Low => 0,
+            RiskLevel::Medium => 1,
+            RiskLevel::High => 2,
+            RiskLevel::Critical => 3,
+        }
+    }
+
+    /// The next more severe risk level (Critical stays Critical)
+    pub fn escalated(&self) -> RiskLevel {
+        match self {
+            RiskLevel::Low => RiskLevel::Medium,
+            RiskLevel::Medium => RiskLevel::High,
+            RiskLevel::High | RiskLevel::Critical => RiskLevel::Critical,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTALLMENT: u64 = 10_000;
+    const GRACE: i64 = LoanProgramState::DEFAULT_GRACE_PERIOD_DAYS as i64 * SECONDS_PER_DAY;
+    const DAILY_BPS: u16 = LoanProgramState::DEFAULT_DAILY_FINE_BPS;
+    const CAP_BPS: u16 = LoanProgramState::DEFAULT_FINE_CAP_BPS;
+
+    #[test]
+    fn no_fine_on_day_zero() {
+        assert_eq!(compute_fine(INSTALLMENT, 0, DAILY_BPS, CAP_BPS, 0), 0);
+        assert_eq!(days_late_after_grace(1_000, 1_000, GRACE), 0);
+    }
+
+    #[test]
+    fn grace_edge_is_not_late() {
+        let due = 1_000;
+        let grace_end = due + GRACE;
+        assert_eq!(days_late_after_grace(due, grace_end, GRACE), 0);
+        assert_eq!(days_late_after_grace(due, grace_end + SECONDS_PER_DAY - 1, GRACE), 0);
+        assert_eq!(days_late_after_grace(due, grace_end + SECONDS_PER_DAY, GRACE), 1);
+        assert_eq!(compute_fine(INSTALLMENT, 1, DAILY_BPS, CAP_BPS, 0), 50);
+    }
+
+    #[test]
+    fn fine_stops_at_the_cap() {
+        // 0.5% a day reaches the 10% ceiling after 20 days
+        assert_eq!(compute_fine(INSTALLMENT, 19, DAILY_BPS, CAP_BPS, 0), 950);
+        assert_eq!(compute_fine(INSTALLMENT, 20, DAILY_BPS, CAP_BPS, 0), 1_000);
+        assert_eq!(compute_fine(INSTALLMENT, 365, DAILY_BPS, CAP_BPS, 0), 1_000);
+        assert_eq!(compute_fine(u64::MAX, u16::MAX, u16::MAX, CAP_BPS, 0), (u64::MAX as u128 * 1000 / 10000) as u64);
+    }
+
+    #[test]
+    fn min_cap_keeps_tiny_installments_fined() {
+        // A /10 cap on a 9-lamport installment is zero, and so is the basis-point cap alone
+        const TINY: u64 = 9;
+        assert_eq!(TINY / 10, 0);
+        assert_eq!(compute_fine(TINY, 200, DAILY_BPS, CAP_BPS, 0), 0);
+
+        // The floor lets the fine accrue, but never past it or the installment itself
+        assert_eq!(compute_fine(TINY, 100, DAILY_BPS, CAP_BPS, 5), 4);
+        assert_eq!(compute_fine(TINY, 200, DAILY_BPS, CAP_BPS, 5), 5);
+        assert_eq!(compute_fine(TINY, 200, DAILY_BPS, CAP_BPS, 1_000), TINY);
+
+        // A floor below the basis-point cap changes nothing
+        assert_eq!(compute_fine(INSTALLMENT, 365, DAILY_BPS, CAP_BPS, 5), 1_000);
+    }
+
+    const PKR: u64 = 1_000_000_000;
+
+    fn new_borrower(employment_type: EmploymentType, monthly_income_pkr: u64) -> UserProfile {
+        UserProfile {
+            authority: Pubkey::default(),
+            full_name: String::new(),
+            monthly_income: monthly_income_pkr * PKR,
+            employment_type,
+            total_loans: 0,
+            active_loans: 0,
+            completed_loans: 0,
+            defaulted_loans: 0,
+            total_borrowed: 0,
+            total_repaid: 0,
+            on_time_payments: 0,
+            late_payments: 0,
+            missed_payments: 0,
+            credit_score: 500,
+            risk_level: RiskLevel::Medium,
+            registration_timestamp: 0,
+            last_updated: 0,
+            monthly_obligations: 0,
+            education_completed: false,
+            contact_hash: [0; 32],
+            notification_opt_in: false,
+            rent_payer: Pubkey::default(),
+            income_change_count: 0,
+            income_change_window_start: 0,
+            co_authority: None,
+            score_window_start: 0,
+            score_window_gain: 0,
+            score_window_loss: 0,
+            early_payments: 0,
+            last_completion_at: 0,
+            last_completion_bonus: 0,
+            guaranteed_obligations: 0,
+            guaranteed_active_loans: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn new_borrower_scores_on_employment_and_income() {
+        assert_eq!(new_borrower(EmploymentType::Salaried, 50_000).computed_risk_score(), 565);
+        assert_eq!(new_borrower(EmploymentType::DailyWage, 10_000).computed_risk_score(), 500);
+        assert_eq!(new_borrower(EmploymentType::Unemployed, 0).computed_risk_score(), 460);
+    }
+
+    #[test]
+    fn clean_repayment_history_scores_high() {
+        let mut profile = new_borrower(EmploymentType::Salaried, 100_000);
+        profile.on_time_payments = 24;
+        profile.completed_loans = 2;
+        profile.total_borrowed = 200_000 * PKR;
+        profile.total_repaid = 220_000 * PKR;
+        // 500 + 96 + 80 + 30 + 40 + 40
+        assert_eq!(profile.computed_risk_score(), 786);
+
+        // Every factor at its best
+        profile.on_time_payments = 100;
+        profile.completed_loans = 10;
+        assert_eq!(profile.computed_risk_score(), 840);
+    }
+
+    #[test]
+    fn defaults_and_missed_payments_floor_the_score() {
+        let mut profile = new_borrower(EmploymentType::SelfEmployed, 20_000);
+        profile.late_payments = 3;
+        profile.missed_payments = 2;
+        profile.total_borrowed = 100_000 * PKR;
+        profile.total_repaid = 25_000 * PKR;
+        // 500 - 30 - 40 + 20 + 20 + 10
+        assert_eq!(profile.computed_risk_score(), 480);
+
+        profile.defaulted_loans = 3;
+        profile.missed_payments = 10;
+        assert_eq!(profile.computed_risk_score(), LoanProgramState::MIN_CREDIT_SCORE);
+    }
+
+    #[test]
+    fn reverting_a_score_change_skips_the_cap_and_frees_the_window() {
+        let mut old = vec![0u8; LoanProgramState::PRE_FINE_PARAMS_LEN];
+        old[..8].copy_from_slice(&LoanProgramState::DISCRIMINATOR);
+        let mut program_state = LoanProgramState::migrate_pre_fine_params(&old).unwrap();
+        program_state.max_score_change_per_window = 10;
+
+        let mut profile = new_borrower(EmploymentType::Salaried, 50_000);
+        profile.score_window_start = 1_000;
+        let first = profile.adjust_credit_score(-8, &program_state, 1_000);
+        let second = profile.adjust_credit_score(-8, &program_state, 1_000);
+        assert_eq!((first, second), (-8, -2));
+        assert_eq!(profile.credit_score, 490);
+        // The window's allowance is spent, so a capped adjustment couldn't undo these
+        assert_eq!(profile.adjust_credit_score(-1, &program_state, 1_000), 0);
+
+        profile.revert_score_change(first, 1_000);
+        assert_eq!(profile.credit_score, 498);
+        assert_eq!(profile.score_window_loss, 2);
+        assert_eq!(profile.adjust_credit_score(-8, &program_state, 1_000), -8);
+
+        // A change from an earlier window is undone without touching this one's counters
+        profile.revert_score_change(5, 0);
+        assert_eq!(profile.credit_score, 485);
+        assert_eq!((profile.score_window_gain, profile.score_window_loss), (0, 10));
+    }
+
+    #[test]
+    fn migration_keeps_old_fields_and_fills_new_defaults() {
+        let old_len = LoanProgramState::PRE_FINE_PARAMS_LEN;
+        let authority = Pubkey::new_unique();
+        let mut old = vec![0u8; old_len];
+        old[..8].copy_from_slice(&LoanProgramState::DISCRIMINATOR);
+        old[8..40].copy_from_slice(&authority.to_bytes());
+        old[48..56].copy_from_slice(&42u64.to_le_bytes()); // total_loans
+        old[old_len - 9..old_len - 1].copy_from_slice(&5_000u64.to_le_bytes()); // max_users
+        old[old_len - 1] = 254;
+
+        let migrated = LoanProgramState::migrate_pre_fine_params(&old).unwrap();
+        assert_eq!(migrated.authority, authority);
+        assert_eq!(migrated.total_loans, 42);
+        assert_eq!(migrated.max_users, 5_000);
+        assert_eq!(migrated.bump, 254);
+        assert_eq!(migrated.grace_period_days, LoanProgramState::DEFAULT_GRACE_PERIOD_DAYS);
+        assert_eq!(migrated.daily_fine_bps, LoanProgramState::DEFAULT_DAILY_FINE_BPS);
+        assert_eq!(migrated.max_active_loans, 1);
+        assert_eq!(migrated.guarantee_dti_weight_bps, LoanProgramState::DEFAULT_GUARANTEE_DTI_WEIGHT_BPS);
+        assert_eq!(migrated.max_guarantees, LoanProgramState::DEFAULT_MAX_GUARANTEES);
+        assert_eq!(migrated.fee_refund_window_days, LoanProgramState::DEFAULT_FEE_REFUND_WINDOW_DAYS);
+        assert_eq!(migrated.payment_waterfall, LoanProgramState::DEFAULT_PAYMENT_WATERFALL);
+        assert_eq!(migrated.bonus_clawback_window_seconds, LoanProgramState::DEFAULT_BONUS_CLAWBACK_WINDOW_SECONDS);
+        assert_eq!(migrated.credit_line_min_completed_loans, LoanProgramState::DEFAULT_CREDIT_LINE_MIN_COMPLETED_LOANS);
+        assert_eq!(migrated.max_restructures_per_loan, LoanProgramState::DEFAULT_MAX_RESTRUCTURES_PER_LOAN);
+        assert_eq!(migrated.score_change_window_seconds, LoanProgramState::DEFAULT_SCORE_CHANGE_WINDOW_SECONDS);
+        assert_eq!(migrated.guarantor_default_penalty, LoanProgramState::DEFAULT_GUARANTOR_DEFAULT_PENALTY);
+        assert_eq!(migrated.max_start_backdate_seconds, LoanProgramState::DEFAULT_MAX_START_BACKDATE_SECONDS);
+        assert_eq!(migrated.max_start_lead_seconds, LoanProgramState::DEFAULT_MAX_START_LEAD_SECONDS);
+        assert_eq!(migrated.fine_cap_bps, LoanProgramState::DEFAULT_FINE_CAP_BPS);
+
+        let mut data = Vec::new();
+        migrated.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), LoanProgramState::LEN);
+    }
+
+    #[test]
+    fn migration_refuses_any_other_layout() {
+        let mut current = vec![0u8; LoanProgramState::LEN];
+        current[..8].copy_from_slice(&LoanProgramState::DISCRIMINATOR);
+        assert!(LoanProgramState::migrate_pre_fine_params(&current).is_err());
+
+        let foreign = vec![0u8; LoanProgramState::PRE_FINE_PARAMS_LEN];
+        assert!(LoanProgramState::migrate_pre_fine_params(&foreign).is_err());
+    }
+
+    #[test]
+    fn waterfall_orders_the_same_part_differently() {
+        // 1_500 against a 400 fine, 300 interest and 900 principal
+        let part = |waterfall: PaymentWaterfall| waterfall.allocate(1_500, 400, 300, 900);
+        assert_eq!(part(PaymentWaterfall::FinesFirst), (400, 300, 800));
+        assert_eq!(part(PaymentWaterfall::InterestFirst), (300, 300, 900));
+        assert_eq!(part(PaymentWaterfall::PrincipalFirst), (300, 300, 900));
+        assert_eq!(PaymentWaterfall::PrincipalFirst.allocate(1_000, 400, 300, 900), (0, 100, 900));
+        assert_eq!(PaymentWaterfall::InterestFirst.allocate(1_000, 400, 300, 900), (0, 300, 700));
+
+        // Anything past what's owed is left for the servicing fee
+        assert_eq!(PaymentWaterfall::FinesFirst.allocate(2_000, 400, 300, 900), (400, 300, 900));
+    }
+
+    #[test]
+    fn default_probability_tracks_the_score() {
+        assert_eq!(default_probability_for(LoanProgramState::MIN_CREDIT_SCORE), 5000);
+        assert_eq!(default_probability_for(LoanProgramState::MAX_CREDIT_SCORE), 100);
+        assert_eq!(default_probability_for(575), 2550);
+        assert_eq!(default_probability_for(0), 5000);
+    }
+}
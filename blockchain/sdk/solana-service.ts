@@ -33,12 +33,18 @@ export interface LoanData {
   interestRate: number;
   tenureMonths: number;
   startTimestamp: number;
+  withInsurance?: boolean;
+  /** Required when the program is configured for floating-rate loans */
+  rateOracle?: PublicKey;
+  /** Per-loan spread over the oracle; defaults to the program's oracle spread */
+  spreadBps?: number;
 }
 
 export interface PaymentData {
   installmentNumber: number;
   amount: number;
   paymentHash: string;
+  paymentRefFormat?: PaymentRefFormat;
 }
 
 export enum EmploymentType {
@@ -56,6 +62,20 @@ export enum LoanStatus {
   Cancelled = 3,
 }
 
+export enum PaymentRefFormat {
+  TxSignature = 0,
+  BankRef = 1,
+  Custom = 2,
+}
+
+export enum CollateralType {
+  Vehicle = 0,
+  Property = 1,
+  Gold = 2,
+  Cash = 3,
+  Other = 4,
+}
+
 export enum RiskLevel {
   Low = 0,
   Medium = 1,
@@ -63,6 +83,23 @@ export enum RiskLevel {
   Critical = 3,
 }
 
+export enum RestructureFineHandling {
+  CarryForward = 0,
+  Waive = 1,
+  Capitalize = 2,
+}
+
+// Anchor encodes enum variants by their camelCase name
+function paymentRefFormatArg(format: PaymentRefFormat): Record<string, {}> {
+  const name = PaymentRefFormat[format];
+  return { [name.charAt(0).toLowerCase() + name.slice(1)]: {} };
+}
+
+function fineHandlingArg(handling: RestructureFineHandling): Record<string, {}> {
+  const name = RestructureFineHandling[handling];
+  return { [name.charAt(0).toLowerCase() + name.slice(1)]: {} };
+}
+
 /**
  * Main Solana Blockchain Service
  * Handles all interactions with the Loan Management Solana program
@@ -180,6 +217,101 @@ export class SolanaBlockchainService {
     );
   }
 
+  /**
+   * Get PDA for an installment's part-payment tracker
+   */
+  getInstallmentStatePDA(
+    loanPubkey: PublicKey,
+    installmentNumber: number
+  ): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('installment'),
+        loanPubkey.toBuffer(),
+        Buffer.from([installmentNumber]),
+      ],
+      this.programId
+    );
+  }
+
+  /**
+   * Get PDA for the collateral locked against a loan
+   */
+  getCollateralPDA(loanPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('collateral'), loanPubkey.toBuffer()],
+      this.programId
+    );
+  }
+
+  /**
+   * Get PDA for a loan's guarantor
+   */
+  getGuarantorPDA(loanPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('guarantor'), loanPubkey.toBuffer()],
+      this.programId
+    );
+  }
+
+  /**
+   * Get PDA for the treasury
+   */
+  getTreasuryPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync([Buffer.from('treasury')], this.programId);
+  }
+
+  /**
+   * Get PDA for a loan's step schedule
+   */
+  getStepSchedulePDA(loanPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('step-schedule'), loanPubkey.toBuffer()],
+      this.programId
+    );
+  }
+
+  /**
+   * Step schedule account to pass for a loan's payments, or null for a level loan
+   */
+  private async stepScheduleFor(loanPubkey: PublicKey): Promise<PublicKey | null> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+    return loan.isStepped ? this.getStepSchedulePDA(loanPubkey)[0] : null;
+  }
+
+  /**
+   * Guarantor accounts to pass whenever a loan leaves (or re-enters) Active, or nulls without a guarantor
+   */
+  private async guarantorAccountsFor(
+    loanPubkey: PublicKey
+  ): Promise<{ guarantor: PublicKey | null; guarantorProfile: PublicKey | null }> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+    if (!loan.hasGuarantor) {
+      return { guarantor: null, guarantorProfile: null };
+    }
+    const [guarantor] = this.getGuarantorPDA(loanPubkey);
+    const { guarantorProfile } = await this.program.account.guarantor.fetch(guarantor);
+    return { guarantor, guarantorProfile };
+  }
+
+  /**
+   * Get PDA for a user's credit line
+   */
+  getCreditLinePDA(userPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('credit-line'), userPubkey.toBuffer()],
+      this.programId
+    );
+  }
+
   /**
    * Get PDA for risk profile
    */
@@ -215,277 +347,972 @@ export class SolanaBlockchainService {
   }
 
   /**
-   * Register a new user on the blockchain
+   * Pause or resume registration and origination (admin only)
    */
-  async registerUser(
-    userKeypair: Keypair,
-    userData: UserProfileData
-  ): Promise<string> {
-    if (!this.program) {
-      throw new Error('Program not initialized');
+  async setPauseState(paused: boolean): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userKeypair.publicKey);
     const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .registerUser(
-        userData.fullName,
-        new BN(userData.monthlyIncome),
-        { [EmploymentType[userData.employmentType].toLowerCase()]: {} }
-      )
+      .setPauseState(paused)
       .accounts({
-        userProfile,
         programState,
-        authority: userKeypair.publicKey,
-        systemProgram: SystemProgram.programId,
+        authority: this.adminKeypair.publicKey,
       })
-      .signers([userKeypair])
+      .signers([this.adminKeypair])
       .rpc();
 
-    console.log('User registered:', tx);
+    console.log('Program pause state set:', tx);
     return tx;
   }
 
   /**
-   * Update user profile
+   * Set the registration cap for a controlled rollout; 0 removes it (admin only)
    */
-  async updateUserProfile(
-    userKeypair: Keypair,
-    monthlyIncome?: number,
-    employmentType?: EmploymentType
-  ): Promise<string> {
-    if (!this.program) {
-      throw new Error('Program not initialized');
+  async setMaxUsers(maxUsers: number): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userKeypair.publicKey);
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .updateUserProfile(
-        monthlyIncome ? new BN(monthlyIncome) : null,
-        employmentType
-          ? { [EmploymentType[employmentType].toLowerCase()]: {} }
-          : null
-      )
+      .setMaxUsers(new BN(maxUsers))
       .accounts({
-        userProfile,
-        authority: userKeypair.publicKey,
+        programState,
+        authority: this.adminKeypair.publicKey,
       })
-      .signers([userKeypair])
+      .signers([this.adminKeypair])
       .rpc();
 
-    console.log('User profile updated:', tx);
+    console.log('Max users set:', tx);
     return tx;
   }
 
   /**
-   * Create a loan for a user (admin only)
+   * Set how many loans a single borrower may hold open at once (admin only)
    */
-  async createLoan(
-    userPubkey: PublicKey,
-    loanData: LoanData,
-    currentLoanCount: number
-  ): Promise<string> {
+  async setMaxActiveLoans(maxActiveLoans: number): Promise<string> {
     if (!this.program || !this.adminKeypair) {
       throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
-    const [loan] = this.getLoanPDA(userPubkey, currentLoanCount);
     const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .createLoan(
-        new BN(loanData.principalAmount),
-        loanData.interestRate,
-        loanData.tenureMonths,
-        new BN(loanData.startTimestamp)
-      )
+      .setMaxActiveLoans(maxActiveLoans)
       .accounts({
-        userProfile,
-        loan,
         programState,
-        userAuthority: userPubkey,
-        admin: this.adminKeypair.publicKey,
-        systemProgram: SystemProgram.programId,
+        authority: this.adminKeypair.publicKey,
       })
       .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Loan created:', tx);
+    console.log('Max active loans set:', tx);
     return tx;
   }
 
   /**
-   * Record a payment for an installment
+   * Create the treasury that receives fines settled on-chain (admin only)
    */
-  async recordPayment(
-    loanPubkey: PublicKey,
-    userPubkey: PublicKey,
-    paymentData: PaymentData,
-    payer: Keypair
-  ): Promise<string> {
-    if (!this.program) {
-      throw new Error('Program not initialized');
+  async initTreasury(): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
-    const [paymentRecord] = this.getPaymentRecordPDA(
-      loanPubkey,
-      paymentData.installmentNumber
-    );
+    const [treasury] = this.getTreasuryPDA();
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .recordPayment(
-        paymentData.installmentNumber,
-        new BN(paymentData.amount),
-        paymentData.paymentHash
-      )
+      .initTreasury()
       .accounts({
-        loan: loanPubkey,
-        userProfile,
-        paymentRecord,
-        user: userPubkey,
-        payer: payer.publicKey,
+        treasury,
+        programState,
+        admin: this.adminKeypair.publicKey,
         systemProgram: SystemProgram.programId,
       })
-      .signers([payer])
+      .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Payment recorded:', tx);
+    console.log('Treasury initialized:', tx);
     return tx;
   }
 
   /**
-   * Update risk score for a user (admin only)
+   * Withdraw accumulated treasury funds to the authority (admin only)
    */
-  async updateRiskScore(
-    userPubkey: PublicKey,
-    riskScore: number,
-    riskLevel: RiskLevel,
-    defaultProbability: number
-  ): Promise<string> {
+  async withdrawTreasury(amount: number): Promise<string> {
     if (!this.program || !this.adminKeypair) {
       throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
-    const [riskProfile] = this.getRiskProfilePDA(userPubkey);
+    const [treasury] = this.getTreasuryPDA();
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .updateRiskScore(
-        riskScore,
-        { [RiskLevel[riskLevel].toLowerCase()]: {} },
-        defaultProbability
-      )
+      .withdrawTreasury(new BN(amount))
       .accounts({
-        userProfile,
-        riskProfile,
-        user: userPubkey,
-        admin: this.adminKeypair.publicKey,
-        systemProgram: SystemProgram.programId,
+        treasury,
+        programState,
+        authority: this.adminKeypair.publicKey,
       })
       .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Risk score updated:', tx);
+    console.log('Treasury withdrawal:', tx);
     return tx;
   }
 
   /**
-   * Mark a loan as defaulted (admin only)
+   * Change the late-fee grace period and daily fine rate (admin only)
    */
-  async markLoanDefaulted(loanPubkey: PublicKey, userPubkey: PublicKey): Promise<string> {
+  async updateFineParams(
+    gracePeriodDays: number | null,
+    dailyFineBps: number | null
+  ): Promise<string> {
     if (!this.program || !this.adminKeypair) {
       throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .markLoanDefaulted()
+      .updateFineParams(gracePeriodDays, dailyFineBps)
       .accounts({
-        loan: loanPubkey,
-        userProfile,
-        admin: this.adminKeypair.publicKey,
+        programState,
+        authority: this.adminKeypair.publicKey,
       })
       .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Loan marked as defaulted:', tx);
+    console.log('Fine params updated:', tx);
     return tx;
   }
 
   /**
-   * Mark a loan as completed
+   * Upgrade a program state created before the fine parameters existed (admin only)
    */
-  async markLoanCompleted(
-    loanPubkey: PublicKey,
-    userPubkey: PublicKey,
-    authority: Keypair
-  ): Promise<string> {
-    if (!this.program) {
-      throw new Error('Program not initialized');
+  async migrateProgramState(): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .markLoanCompleted()
+      .migrateProgramState()
       .accounts({
-        loan: loanPubkey,
-        userProfile,
-        authority: authority.publicKey,
+        programState,
+        authority: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
       })
-      .signers([authority])
+      .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Loan marked as completed:', tx);
+    console.log('Program state migrated:', tx);
     return tx;
   }
 
   /**
-   * Waive fine for an installment (admin only)
+   * Set the mask of employment types barred from borrowing; bit n is EmploymentType n (admin only)
    */
-  async waiveFine(
-    loanPubkey: PublicKey,
-    userPubkey: PublicKey,
-    installmentNumber: number,
-    waivedAmount: number
-  ): Promise<string> {
+  async setEmploymentBlocklist(blockedMask: number): Promise<string> {
     if (!this.program || !this.adminKeypair) {
       throw new Error('Program or admin keypair not initialized');
     }
 
-    const [userProfile] = this.getUserProfilePDA(userPubkey);
-    const [paymentRecord] = this.getPaymentRecordPDA(loanPubkey, installmentNumber);
+    const [programState] = this.getProgramStatePDA();
 
     const tx = await this.program.methods
-      .waiveFine(installmentNumber, new BN(waivedAmount))
+      .setEmploymentBlocklist(blockedMask)
       .accounts({
-        loan: loanPubkey,
-        userProfile,
-        paymentRecord,
-        admin: this.adminKeypair.publicKey,
+        programState,
+        authority: this.adminKeypair.publicKey,
       })
       .signers([this.adminKeypair])
       .rpc();
 
-    console.log('Fine waived:', tx);
+    console.log('Employment blocklist set:', tx);
     return tx;
   }
 
   /**
-   * Fetch user profile
+   * Register a new user on the blockchain
    */
-  async getUserProfile(userPubkey: PublicKey): Promise<any> {
+  async registerUser(
+    userKeypair: Keypair,
+    userData: UserProfileData
+  ): Promise<string> {
     if (!this.program) {
       throw new Error('Program not initialized');
     }
 
-    const [userProfilePDA] = this.getUserProfilePDA(userPubkey);
-    const userProfile = await this.program.account.userProfile.fetch(userProfilePDA);
+    const [userProfile] = this.getUserProfilePDA(userKeypair.publicKey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .registerUser(
+        userData.fullName,
+        new BN(userData.monthlyIncome),
+        { [EmploymentType[userData.employmentType].toLowerCase()]: {} }
+      )
+      .accounts({
+        userProfile,
+        programState,
+        authority: userKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([userKeypair])
+      .rpc();
+
+    console.log('User registered:', tx);
+    return tx;
+  }
+
+  /**
+   * Update user profile
+   */
+  async updateUserProfile(
+    userKeypair: Keypair,
+    monthlyIncome?: number,
+    employmentType?: EmploymentType,
+    /** Primary authority of a joint profile when signing as its co-authority */
+    profileOwner?: PublicKey
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(profileOwner ?? userKeypair.publicKey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .updateUserProfile(
+        monthlyIncome ? new BN(monthlyIncome) : null,
+        employmentType
+          ? { [EmploymentType[employmentType].toLowerCase()]: {} }
+          : null
+      )
+      .accounts({
+        userProfile,
+        programState,
+        authority: userKeypair.publicKey,
+      })
+      .signers([userKeypair])
+      .rpc();
+
+    console.log('User profile updated:', tx);
+    return tx;
+  }
+
+  /**
+   * Create a loan for a user (admin only)
+   */
+  async createLoan(
+    userPubkey: PublicKey,
+    loanData: LoanData,
+    currentLoanCount: number
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [loan] = this.getLoanPDA(userPubkey, currentLoanCount);
+    const [programState] = this.getProgramStatePDA();
+    const [riskProfile] = this.getRiskProfilePDA(userPubkey);
+
+    const tx = await this.program.methods
+      .createLoan(
+        new BN(loanData.principalAmount),
+        loanData.interestRate,
+        loanData.tenureMonths,
+        new BN(loanData.startTimestamp),
+        loanData.withInsurance ?? false,
+        loanData.spreadBps ?? null
+      )
+      .accounts({
+        userProfile,
+        loan,
+        programState,
+        rateOracle: loanData.rateOracle ?? null,
+        riskProfile,
+        userAuthority: userPubkey,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Loan created:', tx);
+    return tx;
+  }
+
+  /**
+   * Approve a credit line for a repeat borrower (admin only)
+   */
+  async openCreditLine(
+    userPubkey: PublicKey,
+    limit: number,
+    interestRate: number
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [creditLine] = this.getCreditLinePDA(userPubkey);
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .openCreditLine(new BN(limit), interestRate)
+      .accounts({
+        creditLine,
+        userProfile,
+        programState,
+        user: userPubkey,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Credit line opened:', tx);
+    return tx;
+  }
+
+  /**
+   * Move an unserviced loan onto stepped installments (admin only)
+   */
+  async createStepSchedule(loanPubkey: PublicKey, multipliersBps: number[]): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [stepSchedule] = this.getStepSchedulePDA(loanPubkey);
+    const [programState] = this.getProgramStatePDA();
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+    const [userProfile] = this.getUserProfilePDA(loan.user);
+
+    const tx = await this.program.methods
+      .createStepSchedule(multipliersBps)
+      .accounts({
+        loan: loanPubkey,
+        stepSchedule,
+        userProfile,
+        programState,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Step schedule created:', tx);
+    return tx;
+  }
+
+  /**
+   * Draw a loan against the borrower's credit line, signed by the borrower
+   */
+  async drawFromCreditLine(
+    userKeypair: Keypair,
+    amount: number,
+    tenureMonths: number,
+    currentLoanCount: number
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [creditLine] = this.getCreditLinePDA(userKeypair.publicKey);
+    const [userProfile] = this.getUserProfilePDA(userKeypair.publicKey);
+    const [loan] = this.getLoanPDA(userKeypair.publicKey, currentLoanCount);
+    const [programState] = this.getProgramStatePDA();
+    const [riskProfile] = this.getRiskProfilePDA(userKeypair.publicKey);
+
+    const tx = await this.program.methods
+      .drawFromCreditLine(new BN(amount), tenureMonths)
+      .accounts({
+        creditLine,
+        userProfile,
+        loan,
+        programState,
+        riskProfile,
+        user: userKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([userKeypair])
+      .rpc();
+
+    console.log('Credit line drawn:', tx);
+    return tx;
+  }
+
+  /**
+   * Record a payment for an installment
+   */
+  async recordPayment(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    paymentData: PaymentData,
+    payer: Keypair
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [paymentRecord] = this.getPaymentRecordPDA(
+      loanPubkey,
+      paymentData.installmentNumber
+    );
+    const [installmentState] = this.getInstallmentStatePDA(
+      loanPubkey,
+      paymentData.installmentNumber
+    );
+    const [programState] = this.getProgramStatePDA();
+    const [treasury] = this.getTreasuryPDA();
+    const hasTreasury = (await this.connection.getAccountInfo(treasury)) !== null;
+
+    const tx = await this.program.methods
+      .recordPayment(
+        paymentData.installmentNumber,
+        new BN(paymentData.amount),
+        paymentData.paymentHash,
+        paymentRefFormatArg(paymentData.paymentRefFormat ?? PaymentRefFormat.Custom)
+      )
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        paymentRecord,
+        installmentState,
+        programState,
+        stepSchedule: await this.stepScheduleFor(loanPubkey),
+        treasury: hasTreasury ? treasury : null,
+        user: userPubkey,
+        payer: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+    console.log('Payment recorded:', tx);
+    return tx;
+  }
+
+  /**
+   * Pay part of an installment; it completes once the parts cover the amount due
+   */
+  async recordPartialPayment(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    installmentNumber: number,
+    amount: number,
+    payer: Keypair
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [installmentState] = this.getInstallmentStatePDA(loanPubkey, installmentNumber);
+    const [paymentRecord] = this.getPaymentRecordPDA(loanPubkey, installmentNumber);
+    const [programState] = this.getProgramStatePDA();
+    const [treasury] = this.getTreasuryPDA();
+    const hasTreasury = (await this.connection.getAccountInfo(treasury)) !== null;
+
+    const tx = await this.program.methods
+      .recordPartialPayment(installmentNumber, new BN(amount))
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        installmentState,
+        paymentRecord,
+        programState,
+        stepSchedule: await this.stepScheduleFor(loanPubkey),
+        treasury: hasTreasury ? treasury : null,
+        user: userPubkey,
+        payer: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+    console.log('Partial payment recorded:', tx);
+    return tx;
+  }
+
+  /**
+   * Update risk score for a user (admin only)
+   */
+  async updateRiskScore(
+    userPubkey: PublicKey,
+    riskScore: number,
+    riskLevel: RiskLevel,
+    defaultProbability: number
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [riskProfile] = this.getRiskProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .updateRiskScore(
+        riskScore,
+        { [RiskLevel[riskLevel].toLowerCase()]: {} },
+        defaultProbability
+      )
+      .accounts({
+        userProfile,
+        riskProfile,
+        programState,
+        user: userPubkey,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Risk score updated:', tx);
+    return tx;
+  }
+
+  /**
+   * Score a user on-chain from their own profile counters (admin only); preferred over updateRiskScore
+   */
+  async computeRiskScore(userPubkey: PublicKey): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [riskProfile] = this.getRiskProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .computeRiskScore()
+      .accounts({
+        userProfile,
+        riskProfile,
+        programState,
+        user: userPubkey,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Risk score computed:', tx);
+    return tx;
+  }
+
+  /**
+   * Restructure an active loan's remaining balance (admin only)
+   */
+  async restructureLoan(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    newTenureMonths: number | null,
+    newInterestRate: number | null,
+    fineHandling: RestructureFineHandling = RestructureFineHandling.CarryForward
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [installmentState] = this.getInstallmentStatePDA(loanPubkey, loan.installmentsPaid + 1);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .restructureLoan(newTenureMonths, newInterestRate, fineHandlingArg(fineHandling))
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        installmentState,
+        programState,
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Loan restructured:', tx);
+    return tx;
+  }
+
+  /**
+   * Close a completed or defaulted loan with its payment records and part-payment trackers,
+   * returning each account's rent to whoever paid it (admin only)
+   */
+  async closeLoan(
+    loanPubkey: PublicKey,
+    paymentRecords: PublicKey[] = []
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    // Each record or part-payment tracker is followed by the account that paid its rent
+    const program = this.program;
+    const remainingAccounts = [];
+    for (const pubkey of paymentRecords) {
+      const { rentPayer } = await program.account.paymentRecord
+        .fetch(pubkey)
+        .catch(() => program.account.installmentState.fetch(pubkey));
+      remainingAccounts.push(
+        { pubkey, isSigner: false, isWritable: true },
+        { pubkey: rentPayer, isSigner: false, isWritable: true }
+      );
+    }
+
+    const tx = await this.program.methods
+      .closeLoan()
+      .accounts({
+        loan: loanPubkey,
+        programState,
+        collateral: loan.isSecured ? this.getCollateralPDA(loanPubkey)[0] : null,
+        rentPayer: loan.rentPayer,
+        admin: this.adminKeypair.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Loan closed:', tx);
+    return tx;
+  }
+
+  /**
+   * Mark a loan as defaulted (admin only)
+   */
+  async markLoanDefaulted(loanPubkey: PublicKey, userPubkey: PublicKey): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+    const loan = await this.program.account.loan.fetch(loanPubkey);
+
+    const tx = await this.program.methods
+      .markLoanDefaulted()
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        programState,
+        collateral: loan.isSecured ? this.getCollateralPDA(loanPubkey)[0] : null,
+        ...(await this.guarantorAccountsFor(loanPubkey)),
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Loan marked as defaulted:', tx);
+    return tx;
+  }
+
+  /**
+   * Lock collateral against an active loan (admin only)
+   */
+  async lockCollateral(
+    loanPubkey: PublicKey,
+    collateralType: CollateralType,
+    declaredValue: number
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [collateral] = this.getCollateralPDA(loanPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .lockCollateral(
+        { [CollateralType[collateralType].toLowerCase()]: {} },
+        new BN(declaredValue)
+      )
+      .accounts({
+        loan: loanPubkey,
+        collateral,
+        programState,
+        admin: this.adminKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Collateral locked:', tx);
+    return tx;
+  }
+
+  /**
+   * Release a completed loan's collateral (admin only)
+   */
+  async releaseCollateral(loanPubkey: PublicKey): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [collateral] = this.getCollateralPDA(loanPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .releaseCollateral()
+      .accounts({
+        loan: loanPubkey,
+        collateral,
+        programState,
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Collateral released:', tx);
+    return tx;
+  }
+
+  /**
+   * Co-sign a loan as its guarantor; the guarantor must already be registered and the
+   * guarantee approved by the borrower or, by default, the admin
+   */
+  async addGuarantor(
+    loanPubkey: PublicKey,
+    guarantorKeypair: Keypair,
+    guaranteedAmount: number,
+    approver?: Keypair
+  ): Promise<string> {
+    const approvedBy = approver ?? this.adminKeypair;
+    if (!this.program || !approvedBy) {
+      throw new Error('Program or approver keypair not initialized');
+    }
+
+    const [guarantorAccount] = this.getGuarantorPDA(loanPubkey);
+    const [guarantorProfile] = this.getUserProfilePDA(guarantorKeypair.publicKey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .addGuarantor(new BN(guaranteedAmount))
+      .accounts({
+        loan: loanPubkey,
+        guarantorAccount,
+        guarantorProfile,
+        programState,
+        approver: approvedBy.publicKey,
+        guarantor: guarantorKeypair.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([guarantorKeypair, approvedBy])
+      .rpc();
+
+    console.log('Guarantor added:', tx);
+    return tx;
+  }
+
+  /**
+   * Mark a loan as completed
+   */
+  async markLoanCompleted(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    authority: Keypair
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .markLoanCompleted()
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        programState,
+        ...(await this.guarantorAccountsFor(loanPubkey)),
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+    console.log('Loan marked as completed:', tx);
+    return tx;
+  }
+
+  /**
+   * Prepay the whole remaining balance of a loan in one payment
+   */
+  async prepayLoan(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    amount: number,
+    payer: Keypair
+  ): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .prepayLoan(new BN(amount))
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        programState,
+        user: userPubkey,
+        ...(await this.guarantorAccountsFor(loanPubkey)),
+        payer: payer.publicKey,
+      })
+      .signers([payer])
+      .rpc();
+
+    console.log('Loan prepaid:', tx);
+    return tx;
+  }
+
+  /**
+   * Cancel a loan that has never been serviced (admin only)
+   */
+  async cancelLoan(loanPubkey: PublicKey, userPubkey: PublicKey): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .cancelLoan()
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        programState,
+        ...(await this.guarantorAccountsFor(loanPubkey)),
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Loan cancelled:', tx);
+    return tx;
+  }
+
+  /**
+   * Waive fine for an installment (admin only)
+   */
+  async waiveFine(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    installmentNumber: number,
+    waivedAmount: number
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [paymentRecord] = this.getPaymentRecordPDA(loanPubkey, installmentNumber);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .waiveFine(installmentNumber, new BN(waivedAmount))
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        paymentRecord,
+        programState,
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Fine waived:', tx);
+    return tx;
+  }
+
+  /**
+   * Reverse the latest installment payment entered in error (admin only)
+   */
+  async reversePayment(
+    loanPubkey: PublicKey,
+    userPubkey: PublicKey,
+    installmentNumber: number,
+    reopen: boolean = false
+  ): Promise<string> {
+    if (!this.program || !this.adminKeypair) {
+      throw new Error('Program or admin keypair not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [paymentRecord] = this.getPaymentRecordPDA(loanPubkey, installmentNumber);
+    const [programState] = this.getProgramStatePDA();
+    const record = await this.program.account.paymentRecord.fetch(paymentRecord);
+
+    const tx = await this.program.methods
+      .reversePayment(installmentNumber, reopen)
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        paymentRecord,
+        rentPayer: record.rentPayer,
+        programState,
+        stepSchedule: await this.stepScheduleFor(loanPubkey),
+        ...(await this.guarantorAccountsFor(loanPubkey)),
+        admin: this.adminKeypair.publicKey,
+      })
+      .signers([this.adminKeypair])
+      .rpc();
+
+    console.log('Payment reversed:', tx);
+    return tx;
+  }
+
+  /**
+   * Re-count a loan in the risk-level stats after its borrower's level changed
+   */
+  async refreshLoanSegment(loanPubkey: PublicKey, userPubkey: PublicKey): Promise<string> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfile] = this.getUserProfilePDA(userPubkey);
+    const [programState] = this.getProgramStatePDA();
+
+    const tx = await this.program.methods
+      .refreshLoanSegment()
+      .accounts({
+        loan: loanPubkey,
+        userProfile,
+        programState,
+      })
+      .rpc();
+
+    console.log('Loan segment refreshed:', tx);
+    return tx;
+  }
+
+  /**
+   * Fetch user profile
+   */
+  async getUserProfile(userPubkey: PublicKey): Promise<any> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [userProfilePDA] = this.getUserProfilePDA(userPubkey);
+    const userProfile = await this.program.account.userProfile.fetch(userProfilePDA);
     return userProfile;
   }
 
@@ -517,6 +1344,54 @@ export class SolanaBlockchainService {
     return paymentRecord;
   }
 
+  /**
+   * Fetch a page of a loan's repayment schedule (at most 24 installments per call)
+   */
+  async getAmortizationSchedule(
+    loanPubkey: PublicKey,
+    startInstallment: number = 1,
+    count: number = 24
+  ): Promise<any[]> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    return this.program.methods
+      .getAmortizationSchedule(startInstallment, count)
+      .accounts({ loan: loanPubkey, stepSchedule: await this.stepScheduleFor(loanPubkey) })
+      .view();
+  }
+
+  /**
+   * Fetch active loans, outstanding balance and defaults by risk level
+   */
+  async getStatsByRiskLevel(): Promise<any> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [programState] = this.getProgramStatePDA();
+    return this.program.methods
+      .getStatsByRiskLevel()
+      .accounts({ programState })
+      .view();
+  }
+
+  /**
+   * Fetch outstanding principal, fines, defaulted and non-performing balances across all loans
+   */
+  async getPortfolioExposure(): Promise<any> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const [programState] = this.getProgramStatePDA();
+    return this.program.methods
+      .getPortfolioExposure()
+      .accounts({ programState })
+      .view();
+  }
+
   /**
    * Fetch risk profile
    */
@@ -543,6 +1418,29 @@ export class SolanaBlockchainService {
     return programState;
   }
 
+  /**
+   * List completed or cancelled loans whose retention period has passed
+   */
+  async getCloseableLoans(): Promise<PublicKey[]> {
+    if (!this.program) {
+      throw new Error('Program not initialized');
+    }
+
+    const { retentionPeriodSeconds } = await this.getProgramState();
+    const now = Math.floor(Date.now() / 1000);
+    const loans = await this.program.account.loan.all();
+
+    return loans
+      .filter(({ account }) => {
+        const endedAt =
+          'completed' in account.status ? account.completedTimestamp
+          : 'cancelled' in account.status ? account.cancelledTimestamp
+          : null;
+        return endedAt !== null && now - endedAt.toNumber() > retentionPeriodSeconds.toNumber();
+      })
+      .map(({ publicKey }) => publicKey);
+  }
+
   /**
    * Get SOL balance
    */